@@ -0,0 +1,249 @@
+use std::f32::consts::PI;
+use std::fs;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time;
+
+use rodio::{Decoder, OutputStream, Sink, Source};
+use serde::Deserialize;
+
+const SAMPLE_RATE: u32 = 44_100;
+
+/// Background track looped during work sessions.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AmbientTrack {
+    /// A soft clock ticking every second
+    Ticking,
+    BrownNoise,
+    File(PathBuf),
+}
+
+impl AmbientTrack {
+    /// Parses a built-in track name, anything else being a file path.
+    pub fn parse(track: &str) -> Self {
+        match track {
+            "ticking" => AmbientTrack::Ticking,
+            "brown-noise" => AmbientTrack::BrownNoise,
+            path => AmbientTrack::File(PathBuf::from(path)),
+        }
+    }
+}
+
+/// Settings of the ambient track, from the `[ambient]` configuration
+/// section.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AmbientSettings {
+    /// `ticking`, `brown-noise` or the path of an audio file
+    pub track: Option<String>,
+    /// Volume of the track, from 0 to 100
+    pub volume: u8,
+}
+
+impl Default for AmbientSettings {
+    fn default() -> Self {
+        AmbientSettings {
+            track: None,
+            volume: 30,
+        }
+    }
+}
+
+/// What the thread owning the audio output is asked to do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Command {
+    Play,
+    Pause,
+}
+
+/// Plays an ambient track in a loop, from a background thread owning the
+/// audio output. The track starts paused.
+pub struct AmbientPlayer {
+    commands: mpsc::Sender<Command>,
+    playing: bool,
+}
+
+impl AmbientPlayer {
+    /// Starts the player, the volume going from 0 to 100.
+    pub fn new(track: AmbientTrack, volume: u8) -> Self {
+        let (commands, receiver) = mpsc::channel();
+        let volume = f32::from(volume.min(100)) / 100.0;
+        thread::spawn(move || play_track(track, volume, receiver));
+        AmbientPlayer::with_commands(commands)
+    }
+
+    /// Player sending its commands to the given channel, instead of an
+    /// audio output.
+    pub(crate) fn with_commands(commands: mpsc::Sender<Command>) -> Self {
+        AmbientPlayer {
+            commands,
+            playing: false,
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn play(&mut self) {
+        if !self.playing {
+            let _ = self.commands.send(Command::Play);
+            self.playing = true;
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if self.playing {
+            let _ = self.commands.send(Command::Pause);
+            self.playing = false;
+        }
+    }
+}
+
+/// Loops the track on the default audio output, playing or pausing it on
+/// the commands received.
+fn play_track(track: AmbientTrack, volume: f32, commands: mpsc::Receiver<Command>) {
+    let Ok((_stream, stream_handle)) = OutputStream::try_default() else {
+        return;
+    };
+    let Ok(sink) = Sink::try_new(&stream_handle) else {
+        return;
+    };
+    sink.pause();
+    sink.set_volume(volume);
+    match track {
+        AmbientTrack::Ticking => sink.append(Ticking::default()),
+        AmbientTrack::BrownNoise => sink.append(BrownNoise::default()),
+        AmbientTrack::File(path) => {
+            let Ok(file) = fs::File::open(path) else {
+                return;
+            };
+            let Ok(decoder) = Decoder::new(BufReader::new(file)) else {
+                return;
+            };
+            sink.append(decoder.repeat_infinite());
+        }
+    }
+    // The sink is dropped, and the track stopped, with the player
+    for command in commands {
+        match command {
+            Command::Play => sink.play(),
+            Command::Pause => sink.pause(),
+        }
+    }
+}
+
+/// A short, muffled click every second.
+#[derive(Default)]
+struct Ticking {
+    sample: u32,
+}
+
+impl Iterator for Ticking {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let t = self.sample as f32 / SAMPLE_RATE as f32;
+        self.sample = (self.sample + 1) % SAMPLE_RATE;
+        Some((2.0 * PI * 1_500.0 * t).sin() * (-t * 400.0).exp() * 0.5)
+    }
+}
+
+impl Source for Ticking {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<time::Duration> {
+        None
+    }
+}
+
+/// Noise with more energy in low frequencies than white noise, obtained by
+/// integrating white noise.
+struct BrownNoise {
+    seed: u32,
+    value: f32,
+}
+
+impl Default for BrownNoise {
+    fn default() -> Self {
+        BrownNoise {
+            seed: 0x9e37_79b9,
+            value: 0.0,
+        }
+    }
+}
+
+impl Iterator for BrownNoise {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        // Xorshift, good enough for noise
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        let white = self.seed as f32 / u32::MAX as f32 * 2.0 - 1.0;
+        // The leak keeps the signal from drifting away from zero
+        self.value = (self.value * 0.998 + white * 0.04).clamp(-1.0, 1.0);
+        Some(self.value)
+    }
+}
+
+impl Source for BrownNoise {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<time::Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ambient_track_parse() {
+        assert_eq!(AmbientTrack::parse("ticking"), AmbientTrack::Ticking);
+        assert_eq!(AmbientTrack::parse("brown-noise"), AmbientTrack::BrownNoise);
+        assert_eq!(
+            AmbientTrack::parse("rain.ogg"),
+            AmbientTrack::File(PathBuf::from("rain.ogg"))
+        );
+    }
+
+    #[test]
+    fn test_generated_tracks_stay_in_range() {
+        let ticking: Vec<f32> = Ticking::default().take(2 * SAMPLE_RATE as usize).collect();
+        let noise: Vec<f32> = BrownNoise::default().take(SAMPLE_RATE as usize).collect();
+        for sample in ticking.iter().chain(&noise) {
+            assert!((-1.0..=1.0).contains(sample));
+        }
+        // The clock ticks at the start of every second, and is silent
+        // in between
+        assert!(ticking[10].abs() > 0.1);
+        assert!(ticking[SAMPLE_RATE as usize + 10].abs() > 0.1);
+        assert!(ticking[SAMPLE_RATE as usize / 2].abs() < 0.01);
+        assert!(noise.iter().any(|sample| sample.abs() > 0.01));
+    }
+}
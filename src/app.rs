@@ -1,72 +1,253 @@
-use crate::ascii_images;
+use crate::history_tab;
+use crate::palette;
+use crate::stats_tab;
 use crossterm::event;
-use ratatui::{layout, style::Stylize, symbols, text, widgets, DefaultTerminal, Frame};
-use std::io;
-use std::path::Path;
+use pomodoro_tui::art::{Animation, ArtPack};
+use pomodoro_tui::commands::{self, Command};
+use pomodoro_tui::config::Profile;
+use pomodoro_tui::history;
+#[cfg(unix)]
+use pomodoro_tui::ipc;
+use pomodoro_tui::keys::{Action, KeyBindings};
+use pomodoro_tui::notifier::NotificationAction;
+use pomodoro_tui::theme::Theme;
+use ratatui::{
+    layout,
+    style::{self, Stylize},
+    symbols, text, widgets, DefaultTerminal, Frame,
+};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
 use std::sync::mpsc;
 use std::time;
 enum Event {
     Key(event::KeyEvent),
+    Mouse(event::MouseEvent),
     Tick,
+    Action(NotificationAction),
+    #[cfg(unix)]
+    Command(ipc::Command),
+}
+
+/// Extra time given to a phase by the snooze action of the notifications.
+const SNOOZE_DURATION: time::Duration = time::Duration::from_secs(5 * 60);
+/// Time between the checks of the timer, and the frames of the art
+const TICK_RATE: time::Duration = time::Duration::from_millis(200);
+
+#[derive(Debug, PartialEq)]
+enum Screen {
+    Timer,
+    History,
+    Stats,
+}
+
+/// Popup drawn over the screen, getting the keys while open.
+enum Overlay {
+    None,
+    Help,
+    Palette(palette::Palette),
 }
 
 pub struct App {
     pomo: pomodoro_tui::Pomodoro,
+    history: history::History,
+    history_tab: history_tab::HistoryTab,
+    screen: Screen,
+    overlay: Overlay,
     exit: bool,
     tx: mpsc::Sender<Event>,
     rx: mpsc::Receiver<Event>,
+    terminal_output: mpsc::Receiver<Vec<u8>>,
+    actions: Option<mpsc::Receiver<NotificationAction>>,
+    #[cfg(unix)]
+    commands: Option<mpsc::Receiver<ipc::Command>>,
+    keys: KeyBindings,
+    theme: Theme,
+    art: ArtPack,
+    /// Frame of the art being shown, if the art is animated
+    animation: Option<Animation>,
+    /// Areas clicked to run an action, from the last draw
+    buttons: Vec<(layout::Rect, Action)>,
+    profiles: BTreeMap<String, Profile>,
     hide_image: bool,
+    gauge_only: bool,
+    /// Whether the timer is always shown on a single line
+    compact: bool,
+    /// Problem shown below the timer, until the next key
+    status: Option<String>,
 }
 
+/// Timer screen fitting in the window, from the full one down to a single
+/// line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TimerLayout {
+    /// Big timers, the active one first, next to the art or not
+    Timers {
+        art: bool,
+        active: tui_big_text::PixelSize,
+        inactive: tui_big_text::PixelSize,
+    },
+    /// Labelled gauges, next to the art or not
+    Gauges {
+        art: bool,
+    },
+    Compact,
+}
+
+/// Sizes of the big timers, active one first, from the largest to the
+/// smallest.
+const TIMER_SIZES: [(tui_big_text::PixelSize, tui_big_text::PixelSize); 3] = [
+    (
+        tui_big_text::PixelSize::Full,
+        tui_big_text::PixelSize::Quadrant,
+    ),
+    (
+        tui_big_text::PixelSize::Quadrant,
+        tui_big_text::PixelSize::Quadrant,
+    ),
+    (
+        tui_big_text::PixelSize::Sextant,
+        tui_big_text::PixelSize::Sextant,
+    ),
+];
+
 impl App {
     pub fn new(
-        work_min: u64,
-        break_min: u64,
+        pomo: pomodoro_tui::Pomodoro,
+        history: history::History,
+        terminal_output: mpsc::Receiver<Vec<u8>>,
+        actions: mpsc::Receiver<NotificationAction>,
         hide_image: bool,
-        sound: &Path,
-        no_sound: bool,
+        gauge_only: bool,
     ) -> Self {
         let (tx, rx) = mpsc::channel();
+        let mut history_tab = history_tab::HistoryTab::new();
+        if history.skipped() > 0 {
+            history_tab.set_status(format!(
+                "Skipped {} invalid lines of the history",
+                history.skipped()
+            ));
+        }
         App {
-            pomo: pomodoro_tui::Pomodoro::new(
-                (work_min, 0),
-                (break_min, 0),
-                sound.to_path_buf(),
-                no_sound,
-            ),
+            pomo,
+            history,
+            history_tab,
+            screen: Screen::Timer,
+            overlay: Overlay::None,
             exit: false,
             tx,
             rx,
+            terminal_output,
+            actions: Some(actions),
+            #[cfg(unix)]
+            commands: None,
+            keys: KeyBindings::default(),
+            theme: Theme::default(),
+            art: ArtPack::default(),
+            animation: Some(Animation::default()),
+            buttons: Vec::new(),
+            profiles: BTreeMap::new(),
             hide_image,
+            gauge_only,
+            compact: false,
+            status: None,
         }
     }
 
-    pub fn run(&mut self, mut terminal: DefaultTerminal) -> io::Result<()> {
+    /// Shows a problem below the timer, like a feature that couldn't be set
+    /// up, until the next key.
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some(status);
+    }
+
+    /// Shows the timer on a single line, whatever the size of the window.
+    pub fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    /// Commands sent by other processes, like `pomodoro-tui ack`.
+    #[cfg(unix)]
+    pub fn set_commands(&mut self, commands: mpsc::Receiver<ipc::Command>) {
+        self.commands = Some(commands);
+    }
+
+    pub fn set_key_bindings(&mut self, keys: KeyBindings) {
+        self.keys = keys;
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn set_art(&mut self, art: ArtPack) {
+        self.art = art;
+    }
+
+    /// Shows only the first frame of the art, for slow terminals.
+    pub fn disable_animation(&mut self) {
+        self.animation = None;
+    }
+
+    /// Profiles applied with the `profile <name>` command.
+    pub fn set_profiles(&mut self, profiles: BTreeMap<String, Profile>) {
+        self.profiles = profiles;
+    }
+
+    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
         while !self.exit {
             terminal.draw(|frame| self.draw(frame))?;
             match self.rx.recv() {
                 Ok(Event::Key(key_event)) => self.handle_key_event(key_event),
-                Ok(Event::Tick) => self.pomo.check_and_switch(),
+                Ok(Event::Mouse(mouse_event)) => self.handle_mouse_event(mouse_event),
+                Ok(Event::Tick) => self.tick(),
+                Ok(Event::Action(action)) => self.handle_action(action),
+                #[cfg(unix)]
+                Ok(Event::Command(ipc::Command::Ack)) => self.pomo.acknowledge(),
                 _ => (),
             }
+            self.write_terminal_output(terminal)?;
         }
         Ok(())
     }
 
-    pub fn handle_inputs(&self) {
+    /// Writes the escape sequences of the terminal notifications, through
+    /// the terminal so that they don't end up in the middle of a draw.
+    fn write_terminal_output(&self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        let mut written = false;
+        while let Ok(bytes) = self.terminal_output.try_recv() {
+            terminal.backend_mut().write_all(&bytes)?;
+            written = true;
+        }
+        if written {
+            terminal.backend_mut().flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn handle_inputs(&mut self) {
+        if let Some(actions) = self.actions.take() {
+            forward(actions, self.tx.clone(), Event::Action);
+        }
+        #[cfg(unix)]
+        if let Some(commands) = self.commands.take() {
+            forward(commands, self.tx.clone(), Event::Command);
+        }
+
         let tx = self.tx.clone();
-        let tick_rate = time::Duration::from_millis(200);
         std::thread::spawn(move || {
             let mut last_tick = time::Instant::now();
             loop {
-                let timeout = tick_rate.saturating_sub(last_tick.elapsed());
+                let timeout = TICK_RATE.saturating_sub(last_tick.elapsed());
                 if event::poll(timeout).unwrap() {
                     match event::read().unwrap() {
                         event::Event::Key(key_event) => tx.send(Event::Key(key_event)).unwrap(),
+                        event::Event::Mouse(mouse_event) => {
+                            tx.send(Event::Mouse(mouse_event)).unwrap()
+                        }
                         _ => (),
                     }
                 }
-                if last_tick.elapsed() >= tick_rate {
+                if last_tick.elapsed() >= TICK_RATE {
                     tx.send(Event::Tick).unwrap();
                     last_tick = time::Instant::now();
                 }
@@ -78,55 +259,369 @@ impl App {
         self.pomo.start_or_pause();
     }
 
-    fn draw(&self, frame: &mut Frame) {
-        let (work_size, work_pixel, break_size, break_pixel) = match self.pomo.state() {
-            pomodoro_tui::PomodoroState::Work => (
-                8,
-                tui_big_text::PixelSize::Full,
-                4,
-                tui_big_text::PixelSize::Quadrant,
-            ),
-            pomodoro_tui::PomodoroState::Break => (
-                4,
-                tui_big_text::PixelSize::Quadrant,
-                8,
-                tui_big_text::PixelSize::Full,
-            ),
-        };
+    fn tick(&mut self) {
+        if let Some(animation) = &mut self.animation {
+            animation.advance(self.art.art(*self.pomo.state()), TICK_RATE);
+        }
+        self.check_and_switch();
+    }
 
+    fn check_and_switch(&mut self) {
+        if let Some(session) = self.pomo.check_and_switch() {
+            if let Err(err) = self.history.add(session) {
+                self.history_tab
+                    .set_status(format!("Failed to save history: {}", err));
+            }
+        }
+    }
+
+    /// Applies the action chosen by the user in a desktop notification.
+    fn handle_action(&mut self, action: NotificationAction) {
+        self.pomo.acknowledge();
+        match action {
+            NotificationAction::Start => {
+                if !self.pomo.is_running() {
+                    self.pomo.start_or_pause();
+                }
+            }
+            NotificationAction::Skip => self.pomo.skip(),
+            NotificationAction::Snooze => self.pomo.snooze(SNOOZE_DURATION),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
         let area = frame.area();
 
-        let block = self.get_block_widget();
+        // The other tabs are drawn as usual, the compact line being the
+        // timer only
+        let timer_layout = self.get_timer_layout(area);
+        if self.screen == Screen::Timer && timer_layout == TimerLayout::Compact {
+            // Clicking the line starts or pauses the timer
+            self.draw_compact(frame, area);
+            self.buttons = vec![(area, Action::StartPause)];
+            self.draw_overlay(frame, area);
+            return;
+        }
+
+        let hints = self.get_hints();
+        let mut buttons = hint_buttons(area, &hints);
+        let block = self.get_block_widget(hints.into_iter().flat_map(|(spans, _)| spans).collect());
+        let inner = block.inner(area);
         frame.render_widget(block, area);
 
-        let (lcenter, rtop, rbottom) = self.get_layout(area, work_size, break_size);
+        match self.screen {
+            Screen::Timer => {
+                let inner = match &self.status {
+                    Some(status) => {
+                        let [timer_area, status_area] = layout::Layout::vertical([
+                            layout::Constraint::Fill(1),
+                            layout::Constraint::Length(1),
+                        ])
+                        .areas(inner);
+                        frame.render_widget(
+                            widgets::Paragraph::new(status.clone().fg(self.theme.error).italic())
+                                .centered(),
+                            status_area,
+                        );
+                        timer_area
+                    }
+                    None => inner,
+                };
+                // Clicking a timer starts or pauses it
+                let timers = self.draw_timer(frame, inner, timer_layout);
+                buttons.extend(timers.map(|timer| (timer, Action::StartPause)));
+            }
+            Screen::History => self
+                .history_tab
+                .draw(frame, inner, &self.history, &self.theme),
+            Screen::Stats => stats_tab::draw(frame, inner, &self.history, &self.theme),
+        }
+        self.buttons = buttons;
+        self.draw_overlay(frame, area);
+    }
+
+    fn draw_overlay(&self, frame: &mut Frame, area: layout::Rect) {
+        match &self.overlay {
+            Overlay::None => (),
+            Overlay::Help => self.draw_help(frame, area),
+            Overlay::Palette(palette) => palette.draw(frame, popup_area(area, 60, 12), &self.theme),
+        }
+    }
+
+    /// Lists the actions with their keys, and the commands of the palette.
+    fn draw_help(&self, frame: &mut Frame, area: layout::Rect) {
+        let messages = self.pomo.messages();
+        let label = |action: Action| match action {
+            Action::StartPause => format!("{} / {}", messages.start, messages.pause),
+            Action::Reset => messages.reset.clone(),
+            Action::Skip => messages.skip.clone(),
+            Action::ToggleAmbient => messages.ambient.clone(),
+            Action::NextTab => messages.next_tab.clone(),
+            Action::Help => messages.help.clone(),
+            Action::Palette => messages.commands.clone(),
+            Action::Quit => messages.quit.clone(),
+        };
+        let mut lines: Vec<text::Line> = Action::ALL
+            .into_iter()
+            .filter_map(|action| {
+                let keys = self.keys.hint(action)?;
+                Some(text::Line::from(vec![
+                    format!("{:>12}  ", format!("<{}>", keys))
+                        .fg(self.theme.hint)
+                        .bold(),
+                    label(action).into(),
+                ]))
+            })
+            .collect();
+        lines.push(text::Line::default());
+        lines.push(text::Line::from(format!(" {}", messages.commands).bold()));
+        lines.extend(
+            commands::USAGES
+                .iter()
+                .map(|usage| text::Line::from(format!("   :{}", usage))),
+        );
+
+        let height = lines.len() as u16 + 2;
+        let area = popup_area(area, 50, height);
+        let block = self
+            .theme
+            .block()
+            .title(text::Line::from(format!(" {} ", messages.help).bold()).centered())
+            .title_bottom(
+                text::Line::from(
+                    format!(" <{}> ", messages.any_key)
+                        .fg(self.theme.hint)
+                        .bold(),
+                )
+                .centered(),
+            );
+        frame.render_widget(widgets::Clear, area);
+        frame.render_widget(widgets::Paragraph::new(lines).block(block), area);
+    }
+
+    /// Largest timer screen fitting inside the borders of the area: the art
+    /// goes first, then the timers get smaller, down to a single line.
+    fn get_timer_layout(&self, area: layout::Rect) -> TimerLayout {
+        let inner = self.theme.block().inner(area);
+        if self.compact || inner.is_empty() {
+            return TimerLayout::Compact;
+        }
+        let art = self.art.art(*self.pomo.state());
+        // The art and the timers share the width in halves
+        let fits = |layout: TimerLayout, (width, height): (u16, u16)| {
+            let art_fits = inner.width / 2 >= (art.width() as u16).max(width)
+                && inner.height >= art.height() as u16;
+            match layout {
+                TimerLayout::Timers { art: true, .. } | TimerLayout::Gauges { art: true } => {
+                    !self.hide_image && art_fits && inner.height >= height
+                }
+                _ => inner.width >= width && inner.height >= height,
+            }
+        };
+        let mut layouts = Vec::new();
+        if self.gauge_only {
+            layouts.extend([
+                TimerLayout::Gauges { art: true },
+                TimerLayout::Gauges { art: false },
+            ]);
+        } else {
+            let (active, inactive) = TIMER_SIZES[0];
+            layouts.push(TimerLayout::Timers {
+                art: true,
+                active,
+                inactive,
+            });
+            layouts.extend(
+                TIMER_SIZES
+                    .iter()
+                    .map(|&(active, inactive)| TimerLayout::Timers {
+                        art: false,
+                        active,
+                        inactive,
+                    }),
+            );
+        }
+        layouts
+            .into_iter()
+            .find(|&layout| {
+                let [(work_width, work_height), (break_width, break_height)] =
+                    self.get_timer_sizes(layout);
+                fits(
+                    layout,
+                    (work_width.max(break_width), work_height + break_height),
+                )
+            })
+            .unwrap_or(TimerLayout::Compact)
+    }
+
+    /// Width and height of the work and break timers of the layout.
+    fn get_timer_sizes(&self, timer_layout: TimerLayout) -> [(u16, u16); 2] {
+        match timer_layout {
+            TimerLayout::Timers {
+                active, inactive, ..
+            } => {
+                let timer_size = |phase, time: String| {
+                    let is_active = *self.pomo.state() == phase;
+                    let pixel = if is_active { active } else { inactive };
+                    let (pixels_wide, pixels_high) = match pixel {
+                        tui_big_text::PixelSize::Full => (1, 1),
+                        tui_big_text::PixelSize::HalfHeight => (1, 2),
+                        tui_big_text::PixelSize::HalfWidth => (2, 1),
+                        tui_big_text::PixelSize::Quadrant => (2, 2),
+                        tui_big_text::PixelSize::ThirdHeight => (1, 3),
+                        tui_big_text::PixelSize::Sextant => (2, 3),
+                    };
+                    // Characters of the font are 8 by 8 pixels, and the
+                    // active timer gets an extra row for its progress gauge
+                    (
+                        (time.chars().count() as u16 * 8).div_ceil(pixels_wide),
+                        8u16.div_ceil(pixels_high) + u16::from(is_active),
+                    )
+                };
+                [
+                    timer_size(pomodoro_tui::PomodoroState::Work, self.pomo.work_time()),
+                    timer_size(pomodoro_tui::PomodoroState::Break, self.pomo.break_time()),
+                ]
+            }
+            // A label like `Break 05:00 ` and a bit of gauge, with a blank
+            // row under it
+            TimerLayout::Gauges { .. } => [(20, 2), (20, 2)],
+            TimerLayout::Compact => [(0, 0), (0, 0)],
+        }
+    }
+
+    /// Draws the timer on a single line like `WORK 12:34 ▮▮▮▯ 2/4`, with
+    /// the progress of the phase and the session in the long break cycle.
+    fn draw_compact(&self, frame: &mut Frame, area: layout::Rect) {
+        let messages = self.pomo.messages();
+        let state = *self.pomo.state();
+        let (time, progress) = match state {
+            pomodoro_tui::PomodoroState::Work => (self.pomo.work_time(), self.pomo.work_progress()),
+            pomodoro_tui::PomodoroState::Break => {
+                (self.pomo.break_time(), self.pomo.break_progress())
+            }
+        };
+        let (session, interval) = self.pomo.cycle_position();
+        let color = self.theme.phase(state);
+        let mut spans = vec![
+            format!("{} ", messages.phase(state).to_uppercase())
+                .fg(color)
+                .bold(),
+            time.bold(),
+        ];
+        let cycle = format!(" {}/{}", session, interval);
+        let alarm = match self.pomo.is_awaiting_ack() {
+            true => format!(" {} <{}>", messages.stop_alarm, messages.any_key),
+            false => String::new(),
+        };
+        // The progress bar takes what is left of the line, up to 8 cells
+        let used: usize = spans.iter().map(text::Span::width).sum::<usize>()
+            + cycle.len()
+            + alarm.chars().count();
+        let cells = (area.width as usize).saturating_sub(used + 1).min(8);
+        if cells > 0 {
+            let filled = ((progress * cells as f64).round() as usize).min(cells);
+            spans.push(" ".into());
+            spans.push("▮".repeat(filled).fg(color));
+            spans.push(text::Span::styled(
+                "▯".repeat(cells - filled),
+                self.theme.dim_style(),
+            ));
+        }
+        spans.push(cycle.into());
+        spans.push(alarm.fg(self.theme.hint).bold());
+
+        let [line_area] = layout::Layout::vertical([layout::Constraint::Length(1)])
+            .flex(layout::Flex::Center)
+            .areas(area);
+        frame.render_widget(widgets::Block::new().style(self.theme.base_style()), area);
+        frame.render_widget(
+            widgets::Paragraph::new(text::Line::from(spans)).centered(),
+            line_area,
+        );
+    }
+
+    /// Draws the timers, and returns their areas.
+    fn draw_timer(
+        &self,
+        frame: &mut Frame,
+        area: layout::Rect,
+        timer_layout: TimerLayout,
+    ) -> [layout::Rect; 2] {
+        let (show_art, work_pixel, break_pixel) = match timer_layout {
+            TimerLayout::Timers {
+                art,
+                active,
+                inactive,
+            } => match self.pomo.state() {
+                pomodoro_tui::PomodoroState::Work => (art, active, inactive),
+                pomodoro_tui::PomodoroState::Break => (art, inactive, active),
+            },
+            TimerLayout::Gauges { art } => (
+                art,
+                tui_big_text::PixelSize::Full,
+                tui_big_text::PixelSize::Full,
+            ),
+            TimerLayout::Compact => unreachable!("the compact timer is drawn on its own"),
+        };
+        let [(_, work_size), (_, break_size)] = self.get_timer_sizes(timer_layout);
+        let (lcenter, rtop, rbottom) = self.get_layout(area, show_art, work_size, break_size);
 
-        if !self.hide_image {
+        if show_art {
             let ascii_img = self.get_ascii_image_widget();
             frame.render_widget(ascii_img, lcenter);
         }
 
+        let (work_gauge, break_gauge) = self.get_gauge_widgets();
+        if self.gauge_only {
+            frame.render_widget(work_gauge, self.get_gauge_area(rtop));
+            frame.render_widget(break_gauge, self.get_gauge_area(rbottom));
+            return [rtop, rbottom];
+        }
+
         let (work_timer, break_timer) = self.get_timer_widgets(work_pixel, break_pixel);
-        frame.render_widget(work_timer, rtop);
-        frame.render_widget(break_timer, rbottom);
+        let (active_area, active_gauge) = match self.pomo.state() {
+            pomodoro_tui::PomodoroState::Work => (rtop, work_gauge),
+            pomodoro_tui::PomodoroState::Break => (rbottom, break_gauge),
+        };
+        let [active_text, gauge_area] =
+            layout::Layout::vertical([layout::Constraint::Fill(1), layout::Constraint::Length(1)])
+                .areas(active_area);
+        let gauge_area = self.get_gauge_area(gauge_area);
+
+        match self.pomo.state() {
+            pomodoro_tui::PomodoroState::Work => {
+                frame.render_widget(work_timer, active_text);
+                frame.render_widget(break_timer, rbottom);
+            }
+            pomodoro_tui::PomodoroState::Break => {
+                frame.render_widget(work_timer, rtop);
+                frame.render_widget(break_timer, active_text);
+            }
+        }
+        frame.render_widget(active_gauge, gauge_area);
+        [rtop, rbottom]
     }
 
     fn get_layout(
         &self,
         area: layout::Rect,
+        show_art: bool,
         work_size: u16,
         break_size: u16,
     ) -> (layout::Rect, layout::Rect, layout::Rect) {
-        let (ascii_width, timer_width) = if !self.hide_image { (50, 50) } else { (0, 100) };
+        let (ascii_width, timer_width) = if show_art { (50, 50) } else { (0, 100) };
         let horizontal = layout::Layout::horizontal([
             layout::Constraint::Percentage(ascii_width),
             layout::Constraint::Percentage(timer_width),
         ]);
         let [left, right] = horizontal.areas(area);
 
+        // Centered vertically, at most art::HEIGHT lines high
+        let art_height = self.art.art(*self.pomo.state()).height() as u16;
         let left_layout = layout::Layout::vertical([
             layout::Constraint::Fill(1),
-            layout::Constraint::Length(10),
+            layout::Constraint::Length(art_height),
             layout::Constraint::Fill(1),
         ]);
         let [_, lcenter, _] = left_layout.areas(left);
@@ -142,37 +637,113 @@ impl App {
         (lcenter, rtop, rbottom)
     }
 
-    fn get_block_widget(&self) -> widgets::Block<'_> {
-        let start_pause = match self.pomo.is_running() {
-            true => "Pause ",
-            false => "Start ",
-        };
+    fn get_gauge_area(&self, area: layout::Rect) -> layout::Rect {
+        let [_, center, _] = layout::Layout::horizontal([
+            layout::Constraint::Percentage(15),
+            layout::Constraint::Percentage(70),
+            layout::Constraint::Percentage(15),
+        ])
+        .areas(area);
+        center
+    }
 
-        let title = text::Line::from(" Pomodoro ".bold());
-        let instructions = text::Line::from(vec![
-            start_pause.into(),
-            "<S>".blue().bold(),
-            " Reset ".into(),
-            "<R>".blue().bold(),
-            " Quit ".into(),
-            "<Q/Esc> ".blue().bold(),
+    fn get_block_widget<'a>(&'a self, hints: text::Line<'a>) -> widgets::Block<'a> {
+        let messages = self.pomo.messages();
+        let tab = |name: &str, screen: Screen| {
+            if self.screen == screen {
+                format!(" {} ", name).bold().reversed()
+            } else {
+                format!(" {} ", name).into()
+            }
+        };
+        let title = text::Line::from(vec![
+            " Pomodoro ".bold(),
+            "│".into(),
+            tab(&messages.timer_tab, Screen::Timer),
+            tab(&messages.history_tab, Screen::History),
+            tab(&messages.stats_tab, Screen::Stats),
         ]);
-        widgets::Block::bordered()
+        self.theme
+            .block()
             .title(title.centered())
-            .title_bottom(instructions.centered())
-            .border_set(symbols::border::THICK)
+            .title_bottom(hints.centered())
     }
 
-    fn get_ascii_image_widget(&self) -> widgets::Paragraph<'_> {
-        let ascii_image: Vec<text::Line> = match self.pomo.state() {
-            pomodoro_tui::PomodoroState::Work => ascii_images::computer(),
-            pomodoro_tui::PomodoroState::Break => ascii_images::sleeping_cat(),
+    /// Hints of the bottom line, with the action run when they are
+    /// clicked.
+    fn get_hints(&self) -> Vec<(Vec<text::Span<'_>>, Option<Action>)> {
+        let messages = self.pomo.messages();
+        // Hints of the actions, for the keys bound to them
+        let hints = |actions: &[(&str, Action)]| {
+            let mut hints = Vec::new();
+            for (label, action) in actions {
+                if let Some(keys) = self.keys.hint(*action) {
+                    let spans = vec![
+                        text::Span::from(format!(" {} ", label)),
+                        format!("<{}>", keys).fg(self.theme.hint).bold(),
+                    ];
+                    hints.push((spans, Some(*action)));
+                }
+            }
+            hints.push((vec![" ".into()], None));
+            hints
+        };
+        match self.screen {
+            _ if self.pomo.is_awaiting_ack() => vec![(
+                vec![
+                    format!(" {} ", messages.stop_alarm).into(),
+                    format!("<{}> ", messages.any_key)
+                        .fg(self.theme.hint)
+                        .bold(),
+                ],
+                None,
+            )],
+            Screen::Timer => {
+                let start_pause = match self.pomo.is_running() {
+                    true => &messages.pause,
+                    false => &messages.start,
+                };
+                let mut actions = vec![
+                    (start_pause.as_str(), Action::StartPause),
+                    (&messages.reset, Action::Reset),
+                ];
+                if self.pomo.has_ambient() {
+                    actions.push((&messages.ambient, Action::ToggleAmbient));
+                }
+                actions.extend([
+                    (messages.next_tab.as_str(), Action::NextTab),
+                    (&messages.help, Action::Help),
+                    (&messages.quit, Action::Quit),
+                ]);
+                hints(&actions)
+            }
+            Screen::History => {
+                let mut tab_hints = vec![(self.history_tab.instructions(&self.theme).spans, None)];
+                // The tab takes every key while searching or editing
+                if !self.history_tab.is_capturing_input() {
+                    tab_hints.extend(hints(&[
+                        (&messages.next_tab, Action::NextTab),
+                        (&messages.help, Action::Help),
+                        (&messages.quit, Action::Quit),
+                    ]));
+                }
+                tab_hints
+            }
+            Screen::Stats => hints(&[
+                (&messages.next_tab, Action::NextTab),
+                (&messages.help, Action::Help),
+                (&messages.quit, Action::Quit),
+            ]),
         }
-        .into_iter()
-        .map(text::Line::from)
-        .collect();
+    }
 
-        widgets::Paragraph::new(ascii_image).alignment(layout::Alignment::Center)
+    fn get_ascii_image_widget(&self) -> widgets::Paragraph<'_> {
+        let art = self.art.art(*self.pomo.state());
+        let lines = match &self.animation {
+            Some(animation) => &animation.frame(art).lines,
+            None => art.lines(),
+        };
+        widgets::Paragraph::new(lines.to_vec()).alignment(layout::Alignment::Center)
     }
 
     fn get_timer_widgets(
@@ -182,28 +753,402 @@ impl App {
     ) -> (tui_big_text::BigText<'_>, tui_big_text::BigText<'_>) {
         let work_timer = tui_big_text::BigText::builder()
             .pixel_size(work_pixel)
-            .lines(vec![self.pomo.work_time().blue().into()])
+            .lines(vec![self.pomo.work_time().fg(self.theme.work).into()])
             .centered()
             .build();
         let break_timer = tui_big_text::BigText::builder()
             .pixel_size(break_pixel)
-            .lines(vec![self.pomo.break_time().green().into()])
+            .lines(vec![self
+                .pomo
+                .break_time()
+                .fg(self.theme.break_time)
+                .into()])
             .centered()
             .build();
         (work_timer, break_timer)
     }
 
+    fn get_gauge_widgets(&self) -> (widgets::LineGauge<'_>, widgets::LineGauge<'_>) {
+        let (work_label, break_label) = if self.gauge_only {
+            // The names are padded to the same width, to align the gauges
+            let messages = self.pomo.messages();
+            let names = [
+                pomodoro_tui::PomodoroState::Work,
+                pomodoro_tui::PomodoroState::Break,
+            ]
+            .map(|state| messages.phase(state));
+            let width = names
+                .iter()
+                .map(|name| name.chars().count())
+                .max()
+                .unwrap_or(0);
+            (
+                format!("{:width$} {} ", capitalize(names[0]), self.pomo.work_time()),
+                format!(
+                    "{:width$} {} ",
+                    capitalize(names[1]),
+                    self.pomo.break_time()
+                ),
+            )
+        } else {
+            (String::new(), String::new())
+        };
+        let work_gauge = widgets::LineGauge::default()
+            .filled_style(style::Style::new().fg(self.theme.work))
+            .unfilled_style(self.theme.dim_style())
+            .line_set(symbols::line::THICK)
+            .label(work_label)
+            .ratio(self.pomo.work_progress());
+        let break_gauge = widgets::LineGauge::default()
+            .filled_style(style::Style::new().fg(self.theme.break_time))
+            .unfilled_style(self.theme.dim_style())
+            .line_set(symbols::line::THICK)
+            .label(break_label)
+            .ratio(self.pomo.break_progress());
+        (work_gauge, break_gauge)
+    }
+
     fn handle_key_event(&mut self, key_event: event::KeyEvent) {
-        match key_event.code {
-            event::KeyCode::Char('s') => {
-                self.pomo.start_or_pause();
+        self.status = None;
+        // Any key stops an alarm ringing until acknowledged
+        if self.pomo.is_awaiting_ack() {
+            self.pomo.acknowledge();
+            return;
+        }
+        match &mut self.overlay {
+            Overlay::None => (),
+            // Any key closes the help
+            Overlay::Help => {
+                self.overlay = Overlay::None;
+                return;
+            }
+            Overlay::Palette(palette) => {
+                match palette.handle_key_event(key_event) {
+                    palette::Outcome::Pending => (),
+                    palette::Outcome::Close => self.overlay = Overlay::None,
+                    palette::Outcome::Run(command) => {
+                        // The palette stays open with the command typed if
+                        // it fails
+                        let overlay = std::mem::replace(&mut self.overlay, Overlay::None);
+                        if let Err(err) = self.run_command(command) {
+                            if let Overlay::Palette(mut palette) = overlay {
+                                palette.set_error(err);
+                                self.overlay = Overlay::Palette(palette);
+                            }
+                        }
+                    }
+                }
+                return;
+            }
+        }
+        if self.screen == Screen::History && self.history_tab.is_capturing_input() {
+            self.history_tab
+                .handle_key_event(key_event, &mut self.history);
+            return;
+        }
+        match self.keys.action(&key_event) {
+            Some(action @ (Action::NextTab | Action::Help | Action::Palette | Action::Quit)) => {
+                self.execute(Command::Action(action));
+            }
+            action => match self.screen {
+                Screen::Timer => {
+                    if let Some(action) = action {
+                        self.execute(Command::Action(action));
+                    }
+                }
+                Screen::History => self
+                    .history_tab
+                    .handle_key_event(key_event, &mut self.history),
+                Screen::Stats => (),
+            },
+        }
+    }
+
+    fn handle_mouse_event(&mut self, mouse_event: event::MouseEvent) {
+        match mouse_event.kind {
+            event::MouseEventKind::Down(event::MouseButton::Left) => {
+                // Like any key, a click stops an alarm ringing until
+                // acknowledged, and closes the help
+                if self.pomo.is_awaiting_ack() {
+                    self.pomo.acknowledge();
+                    return;
+                }
+                match self.overlay {
+                    Overlay::None => (),
+                    Overlay::Help => {
+                        self.overlay = Overlay::None;
+                        return;
+                    }
+                    Overlay::Palette(_) => return,
+                }
+                let position = layout::Position::new(mouse_event.column, mouse_event.row);
+                if let Some(&(_, action)) = self
+                    .buttons
+                    .iter()
+                    .find(|(area, _)| area.contains(position))
+                {
+                    self.execute(Command::Action(action));
+                }
+            }
+            event::MouseEventKind::ScrollDown if self.screen == Screen::History => {
+                self.history_tab.select_next(&self.history);
             }
-            event::KeyCode::Char('r') => {
-                self.pomo.reset();
+            event::MouseEventKind::ScrollUp if self.screen == Screen::History => {
+                self.history_tab.select_previous();
             }
-            event::KeyCode::Esc => self.exit = true,
-            event::KeyCode::Char('q') => self.exit = true,
             _ => (),
         }
     }
+
+    /// Runs a command bound to a key. The palette shows why it failed, if it
+    /// did.
+    fn execute(&mut self, command: Command) {
+        if let Err(err) = self.run_command(command) {
+            let mut palette = palette::Palette::new();
+            palette.set_error(err);
+            self.overlay = Overlay::Palette(palette);
+        }
+    }
+
+    fn run_command(&mut self, command: Command) -> Result<(), String> {
+        match command {
+            Command::Action(Action::StartPause) => self.pomo.start_or_pause(),
+            Command::Action(Action::Reset) => self.pomo.reset(),
+            Command::Action(Action::Skip) => self.pomo.skip(),
+            Command::Action(Action::ToggleAmbient) => self.pomo.toggle_ambient(),
+            Command::Action(Action::NextTab) => {
+                self.screen = match self.screen {
+                    Screen::Timer => Screen::History,
+                    Screen::History => Screen::Stats,
+                    Screen::Stats => Screen::Timer,
+                };
+            }
+            Command::Action(Action::Help) => self.overlay = Overlay::Help,
+            Command::Action(Action::Palette) => {
+                self.overlay = Overlay::Palette(palette::Palette::new());
+            }
+            Command::Action(Action::Quit) => self.exit = true,
+            Command::SetWork(duration) => self.pomo.set_work_duration(duration),
+            Command::SetBreak(duration) => self.pomo.set_break_duration(duration),
+            Command::SetLongBreak(duration) => self.pomo.set_long_break_duration(duration),
+            Command::Task(task) => self.pomo.set_task(task),
+            Command::TagAdd(tag) => {
+                let mut tags = self.pomo.tags().to_vec();
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+                self.pomo.set_tags(tags);
+            }
+            Command::TagRemove(tag) => {
+                let mut tags = self.pomo.tags().to_vec();
+                let count = tags.len();
+                tags.retain(|other| *other != tag);
+                if tags.len() == count {
+                    return Err(format!("no tag `{}`", tag));
+                }
+                self.pomo.set_tags(tags);
+            }
+            Command::Profile(name) => {
+                let profile = self
+                    .profiles
+                    .get(&name)
+                    .ok_or_else(|| format!("unknown profile `{}`", name))?;
+                self.pomo.apply_profile(profile);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Name with its first letter in upper case, like `Work`.
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Areas of the hints of actions, in the bottom line of the given area
+/// where they are centered.
+fn hint_buttons(
+    area: layout::Rect,
+    hints: &[(Vec<text::Span>, Option<Action>)],
+) -> Vec<(layout::Rect, Action)> {
+    let width = |spans: &[text::Span]| spans.iter().map(text::Span::width).sum::<usize>() as u16;
+    let total_width: u16 = hints.iter().map(|(spans, _)| width(spans)).sum();
+    // Inside the borders of the block
+    let mut x = area.x + 1 + area.width.saturating_sub(2).saturating_sub(total_width) / 2;
+    let y = area.bottom().saturating_sub(1);
+    let mut buttons = Vec::new();
+    for (spans, action) in hints {
+        let width = width(spans);
+        if let Some(action) = action {
+            buttons.push((
+                layout::Rect::new(x, y, width, 1).intersection(area),
+                *action,
+            ));
+        }
+        x += width;
+    }
+    buttons
+}
+
+/// Area of the given size, centered in the given area and fitting in it.
+fn popup_area(area: layout::Rect, width: u16, height: u16) -> layout::Rect {
+    let [area] = layout::Layout::horizontal([layout::Constraint::Length(width)])
+        .flex(layout::Flex::Center)
+        .areas(area);
+    let [area] = layout::Layout::vertical([layout::Constraint::Length(height)])
+        .flex(layout::Flex::Center)
+        .areas(area);
+    area
+}
+
+/// Sends the values received on a channel as events, from a background
+/// thread.
+fn forward<T: Send + 'static>(
+    receiver: mpsc::Receiver<T>,
+    tx: mpsc::Sender<Event>,
+    event: fn(T) -> Event,
+) {
+    std::thread::spawn(move || {
+        for value in receiver {
+            if tx.send(event(value)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(history_path: &std::path::Path) -> App {
+        let _ = std::fs::remove_file(history_path);
+        let mut history = history::History::load(history_path).unwrap();
+        history
+            .add(history::Session {
+                start: chrono::Local::now(),
+                phase: pomodoro_tui::PomodoroState::Work,
+                duration: 25 * 60,
+                task: Some("write tests".to_string()),
+                tags: Vec::new(),
+                notes: String::new(),
+            })
+            .unwrap();
+        let pomo = pomodoro_tui::Pomodoro::new(
+            (25, 0),
+            (5, 0),
+            pomodoro_tui::sounds::Sound::default(),
+            true,
+            Box::new(pomodoro_tui::notifier::NoopNotifier),
+        );
+        App::new(
+            pomo,
+            history,
+            mpsc::channel().1,
+            mpsc::channel().1,
+            false,
+            false,
+        )
+    }
+
+    fn render(app: &mut App, width: u16, height: u16) -> String {
+        let backend = ratatui::backend::TestBackend::new(width, height);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_compact_history_tab() {
+        // Given
+        let path = std::env::temp_dir().join(format!(
+            "pomodoro-tui-app-compact-{}.jsonl",
+            std::process::id()
+        ));
+        let mut app = app(&path);
+        app.set_compact(true);
+        // When
+        let timer = render(&mut app, 80, 12);
+        app.screen = Screen::History;
+        let history = render(&mut app, 80, 12);
+        std::fs::remove_file(&path).unwrap();
+        // Then the timer is on one line, and the history is drawn as usual
+        assert!(timer.contains("WORK 25:00"));
+        assert!(history.contains("write tests"));
+        assert!(!history.contains("WORK 25:00"));
+    }
+
+    #[test]
+    fn test_history_tab_hints_are_buttons() {
+        // Given
+        let path = std::env::temp_dir().join(format!(
+            "pomodoro-tui-app-hints-{}.jsonl",
+            std::process::id()
+        ));
+        let mut app = app(&path);
+        app.screen = Screen::History;
+        render(&mut app, 140, 20);
+        std::fs::remove_file(&path).unwrap();
+        // When the next tab hint is clicked
+        let &(area, _) = app
+            .buttons
+            .iter()
+            .find(|(_, action)| *action == Action::NextTab)
+            .unwrap();
+        app.handle_mouse_event(event::MouseEvent {
+            kind: event::MouseEventKind::Down(event::MouseButton::Left),
+            column: area.x,
+            row: area.y,
+            modifiers: event::KeyModifiers::NONE,
+        });
+        // Then
+        assert_eq!(app.screen, Screen::Stats);
+    }
+
+    #[test]
+    fn test_gauge_labels_are_localized() {
+        // Given
+        let path = std::env::temp_dir().join(format!(
+            "pomodoro-tui-app-gauges-{}.jsonl",
+            std::process::id()
+        ));
+        let mut app = app(&path);
+        std::fs::remove_file(&path).unwrap();
+        app.gauge_only = true;
+        app.pomo.set_messages(pomodoro_tui::messages::Messages::new(
+            pomodoro_tui::messages::Language::Spanish,
+        ));
+        // When
+        let screen = render(&mut app, 80, 20);
+        // Then the names are aligned
+        assert!(screen.contains("Trabajo  25:00"));
+        assert!(screen.contains("Descanso 05:00"));
+    }
+
+    #[test]
+    fn test_status_until_next_key() {
+        // Given
+        let path = std::env::temp_dir().join(format!(
+            "pomodoro-tui-app-status-{}.jsonl",
+            std::process::id()
+        ));
+        let mut app = app(&path);
+        std::fs::remove_file(&path).unwrap();
+        // When
+        app.set_status("`pomodoro-tui ack` won't reach this timer".to_string());
+        // Then
+        assert!(render(&mut app, 80, 20).contains("won't reach this timer"));
+        app.handle_key_event(event::KeyEvent::from(event::KeyCode::Char('x')));
+        assert!(!render(&mut app, 80, 20).contains("won't reach this timer"));
+    }
 }
@@ -0,0 +1,551 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time;
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use serde::Deserialize;
+
+use crate::{ascii_images, PomodoroState};
+
+/// Number of lines of the area where the art is drawn.
+pub const HEIGHT: usize = 10;
+
+/// Duration of the frames of an animated art, unless set.
+pub const FRAME_DURATION: time::Duration = time::Duration::from_millis(500);
+
+/// One drawing of an art, shown for its duration when animated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub lines: Vec<Line<'static>>,
+    pub duration: time::Duration,
+}
+
+/// A drawing shown next to the timers, with its colors, animated when it
+/// has several frames.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Art {
+    frames: Vec<Frame>,
+}
+
+impl Art {
+    /// Makes an art of the frames, padded to the same width and height to
+    /// stay aligned once centered.
+    fn new(mut frames: Vec<Frame>) -> Result<Self, String> {
+        for frame in &mut frames {
+            while frame.lines.last().is_some_and(|line| line.width() == 0) {
+                frame.lines.pop();
+            }
+            if frame.lines.is_empty() {
+                return Err("the art has an empty frame".to_string());
+            }
+        }
+        if frames.is_empty() {
+            return Err("the art is empty".to_string());
+        }
+        let height = frames
+            .iter()
+            .map(|frame| frame.lines.len())
+            .max()
+            .unwrap_or(0);
+        if height > HEIGHT {
+            return Err(format!(
+                "the art has {} lines, at most {} fit",
+                height, HEIGHT
+            ));
+        }
+        let width = frames
+            .iter()
+            .flat_map(|frame| frame.lines.iter().map(Line::width))
+            .max()
+            .unwrap_or(0);
+        for frame in &mut frames {
+            frame.lines.resize(height, Line::default());
+            for line in &mut frame.lines {
+                let padding = width - line.width();
+                if padding > 0 {
+                    line.push_span(Span::raw(" ".repeat(padding)));
+                }
+            }
+        }
+        Ok(Art { frames })
+    }
+
+    /// Parses a drawing, where ANSI escape sequences set the colors. A line
+    /// starting with `%%` ends a frame, followed by its duration in
+    /// milliseconds if it isn't the default one, like `%% 300ms`.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut frames = Vec::new();
+        let mut lines = Vec::new();
+        let mut style = Style::new();
+        for line in content.lines() {
+            let Some(duration) = line.strip_prefix("%%") else {
+                lines.push(parse_ansi_line(line, &mut style));
+                continue;
+            };
+            frames.push(Frame {
+                lines: std::mem::take(&mut lines),
+                duration: parse_frame_duration(duration.trim())?,
+            });
+            // Each frame starts without colors
+            style = Style::new();
+        }
+        // The last frame doesn't need to be ended
+        if frames.is_empty() || lines.iter().any(|line| line.width() > 0) {
+            frames.push(Frame {
+                lines,
+                duration: FRAME_DURATION,
+            });
+        }
+        Art::new(frames)
+    }
+
+    /// Loads a drawing from a text file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Art::parse(&content).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid art in {}: {}", path.display(), err),
+            )
+        })
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Lines of the first frame, for an art which isn't animated.
+    pub fn lines(&self) -> &[Line<'static>] {
+        &self.frames[0].lines
+    }
+
+    /// Width of the frames.
+    pub fn width(&self) -> usize {
+        self.frames[0].lines[0].width()
+    }
+
+    /// Height of the frames.
+    pub fn height(&self) -> usize {
+        self.frames[0].lines.len()
+    }
+
+    /// Same art, with the colors of the terminal.
+    pub fn without_colors(mut self) -> Self {
+        let spans = self
+            .frames
+            .iter_mut()
+            .flat_map(|frame| &mut frame.lines)
+            .flat_map(|line| &mut line.spans);
+        for span in spans {
+            span.style.fg = None;
+            span.style.bg = None;
+        }
+        self
+    }
+}
+
+fn parse_frame_duration(duration: &str) -> Result<time::Duration, String> {
+    if duration.is_empty() {
+        return Ok(FRAME_DURATION);
+    }
+    match duration.trim_end_matches("ms").trim().parse() {
+        Ok(0) | Err(_) => Err(format!("invalid frame duration `{}`", duration)),
+        Ok(milliseconds) => Ok(time::Duration::from_millis(milliseconds)),
+    }
+}
+
+/// Frame of an art to show, going to the next one once its duration is
+/// over.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Animation {
+    frame: usize,
+    /// Time the frame has been shown for
+    elapsed: time::Duration,
+}
+
+impl Animation {
+    /// Lets the given time go by.
+    pub fn advance(&mut self, art: &Art, time: time::Duration) {
+        let frames = art.frames();
+        self.elapsed += time;
+        loop {
+            self.frame %= frames.len();
+            let duration = frames[self.frame].duration;
+            if self.elapsed < duration {
+                break;
+            }
+            self.elapsed -= duration;
+            self.frame += 1;
+        }
+    }
+
+    pub fn frame<'a>(&self, art: &'a Art) -> &'a Frame {
+        &art.frames()[self.frame % art.frames().len()]
+    }
+}
+
+/// Art of each phase.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtPack {
+    pub work: Art,
+    pub break_time: Art,
+}
+
+impl Default for ArtPack {
+    /// The computer during work sessions, and the sleeping cat during breaks.
+    fn default() -> Self {
+        let builtin = |frames: Vec<Vec<String>>, milliseconds| {
+            let frames = frames
+                .into_iter()
+                .map(|lines| Frame {
+                    lines: lines.into_iter().map(Line::from).collect(),
+                    duration: time::Duration::from_millis(milliseconds),
+                })
+                .collect();
+            Art::new(frames).expect("the built-in art is valid")
+        };
+        ArtPack {
+            work: builtin(ascii_images::computer_frames(), 600),
+            break_time: builtin(ascii_images::sleeping_cat_frames(), 800),
+        }
+    }
+}
+
+impl ArtPack {
+    /// Directory of the packs, in the user configuration directory.
+    pub fn packs_dir() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("pomodoro-tui")
+            .join("art")
+    }
+
+    /// Loads the pack of a directory, with a `work.txt` and a `break.txt`
+    /// file. The built-in art replaces a missing one.
+    pub fn load(dir: &Path) -> io::Result<Self> {
+        let work = dir.join("work.txt");
+        let break_time = dir.join("break.txt");
+        if !work.is_file() && !break_time.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no work.txt nor break.txt in {}", dir.display()),
+            ));
+        }
+        let mut pack = ArtPack::default();
+        if work.is_file() {
+            pack.work = Art::load(&work)?;
+        }
+        if break_time.is_file() {
+            pack.break_time = Art::load(&break_time)?;
+        }
+        Ok(pack)
+    }
+
+    /// Pack of the settings, the files of the settings replacing the art of
+    /// the pack.
+    pub fn from_settings(settings: &ArtSettings) -> io::Result<Self> {
+        let mut pack = match &settings.pack {
+            // A pack is either a directory, or the name of one in the packs
+            // directory
+            Some(pack) if Path::new(pack).is_dir() => ArtPack::load(Path::new(pack))?,
+            Some(pack) => ArtPack::load(&ArtPack::packs_dir().join(pack))?,
+            None => ArtPack::default(),
+        };
+        if let Some(path) = &settings.work {
+            pack.work = Art::load(path)?;
+        }
+        if let Some(path) = &settings.break_time {
+            pack.break_time = Art::load(path)?;
+        }
+        Ok(pack)
+    }
+
+    pub fn art(&self, phase: PomodoroState) -> &Art {
+        match phase {
+            PomodoroState::Work => &self.work,
+            PomodoroState::Break => &self.break_time,
+        }
+    }
+
+    /// Same pack, with the colors of the terminal, for `NO_COLOR`.
+    pub fn without_colors(self) -> Self {
+        ArtPack {
+            work: self.work.without_colors(),
+            break_time: self.break_time.without_colors(),
+        }
+    }
+}
+
+/// Art of the `[art]` configuration section.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArtSettings {
+    /// Directory of a pack, or name of one in the packs directory
+    pub pack: Option<String>,
+    /// Text files of the art of each phase
+    pub work: Option<PathBuf>,
+    #[serde(rename = "break")]
+    pub break_time: Option<PathBuf>,
+    /// Whether art with several frames is animated
+    pub animate: bool,
+}
+
+impl Default for ArtSettings {
+    fn default() -> Self {
+        ArtSettings {
+            pack: None,
+            work: None,
+            break_time: None,
+            animate: true,
+        }
+    }
+}
+
+/// Splits a line into spans of the style set by its SGR escape sequences,
+/// starting with the given style, which is left as the line ends. Other
+/// escape sequences are dropped.
+fn parse_ansi_line(line: &str, style: &mut Style) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut text = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            text.push(c);
+            continue;
+        }
+        if !text.is_empty() {
+            spans.push(Span::styled(std::mem::take(&mut text), *style));
+        }
+        if chars.next_if_eq(&'[').is_none() {
+            // Escape sequence of two characters
+            chars.next();
+            continue;
+        }
+        let mut params = String::new();
+        for c in chars.by_ref() {
+            if ('@'..='~').contains(&c) {
+                if c == 'm' {
+                    apply_sgr(style, &params);
+                }
+                break;
+            }
+            params.push(c);
+        }
+    }
+    if !text.is_empty() {
+        spans.push(Span::styled(text, *style));
+    }
+    Line::from(spans)
+}
+
+fn apply_sgr(style: &mut Style, params: &str) {
+    // An empty code is a reset, and an invalid one is skipped
+    let codes: Vec<Option<u8>> = params
+        .split(';')
+        .map(|code| match code {
+            "" => Some(0),
+            code => code.parse().ok(),
+        })
+        .collect();
+    let mut codes = codes.into_iter();
+    while let Some(code) = codes.next() {
+        let Some(code) = code else {
+            continue;
+        };
+        match code {
+            0 => *style = Style::new(),
+            1 => *style = style.add_modifier(Modifier::BOLD),
+            2 => *style = style.add_modifier(Modifier::DIM),
+            3 => *style = style.add_modifier(Modifier::ITALIC),
+            4 => *style = style.add_modifier(Modifier::UNDERLINED),
+            5 => *style = style.add_modifier(Modifier::SLOW_BLINK),
+            7 => *style = style.add_modifier(Modifier::REVERSED),
+            9 => *style = style.add_modifier(Modifier::CROSSED_OUT),
+            22 => *style = style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => *style = style.remove_modifier(Modifier::ITALIC),
+            24 => *style = style.remove_modifier(Modifier::UNDERLINED),
+            25 => *style = style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => *style = style.remove_modifier(Modifier::REVERSED),
+            29 => *style = style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => *style = style.fg(Color::Indexed(code - 30)),
+            38 => {
+                if let Some(color) = extended_color(&mut codes) {
+                    *style = style.fg(color);
+                }
+            }
+            // Back to the colors of the theme
+            39 => style.fg = None,
+            40..=47 => *style = style.bg(Color::Indexed(code - 40)),
+            48 => {
+                if let Some(color) = extended_color(&mut codes) {
+                    *style = style.bg(color);
+                }
+            }
+            49 => style.bg = None,
+            90..=97 => *style = style.fg(Color::Indexed(code - 90 + 8)),
+            100..=107 => *style = style.bg(Color::Indexed(code - 100 + 8)),
+            _ => (),
+        }
+    }
+}
+
+/// Color of the 256 colors (`5;n`) or RGB (`2;r;g;b`) forms.
+fn extended_color(codes: &mut impl Iterator<Item = Option<u8>>) -> Option<Color> {
+    match codes.next()?? {
+        5 => Some(Color::Indexed(codes.next()??)),
+        2 => Some(Color::Rgb(codes.next()??, codes.next()??, codes.next()??)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(art: &Art) -> Vec<String> {
+        art.lines().iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_art_parse() {
+        // When
+        let art = Art::parse("  /\\_/\\\n ( o.o )\n  > ^ <\n\n").unwrap();
+        // Then the lines have the same width, and trailing empty lines are
+        // dropped
+        assert_eq!(text(&art), vec!["  /\\_/\\ ", " ( o.o )", "  > ^ < "]);
+    }
+
+    #[test]
+    fn test_art_parse_ansi_colors() {
+        // When
+        let art = Art::parse("\x1b[1;31mred\x1b[0m plain\n\x1b[38;5;208morange\x1b[K").unwrap();
+        // Then
+        let first = &art.lines()[0].spans;
+        assert_eq!(first[0].content, "red");
+        assert_eq!(
+            first[0].style,
+            Style::new()
+                .add_modifier(Modifier::BOLD)
+                .fg(Color::Indexed(1))
+        );
+        assert_eq!(first[1].content, " plain");
+        assert_eq!(first[1].style, Style::new());
+        let second = &art.lines()[1].spans;
+        assert_eq!(second[0].content, "orange");
+        assert_eq!(second[0].style, Style::new().fg(Color::Indexed(208)));
+        assert_eq!(art.lines()[1].width(), 9);
+    }
+
+    #[test]
+    fn test_art_parse_invalid_ansi_codes() {
+        // When
+        let art = Art::parse("\x1b[31m\x1b[300;1mred\x1b[;4mplain").unwrap();
+        // Then the invalid code is skipped, and the empty one resets
+        let spans = &art.lines()[0].spans;
+        assert_eq!(
+            spans[0].style,
+            Style::new()
+                .fg(Color::Indexed(1))
+                .add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            spans[1].style,
+            Style::new().add_modifier(Modifier::UNDERLINED)
+        );
+    }
+
+    #[test]
+    fn test_art_without_colors() {
+        let art = Art::parse("\x1b[1;31;42mred").unwrap().without_colors();
+        assert_eq!(
+            art.lines()[0].spans[0].style,
+            Style::new().add_modifier(Modifier::BOLD)
+        );
+    }
+
+    #[test]
+    fn test_art_parse_too_high() {
+        assert_eq!(
+            Art::parse(&"x\n".repeat(12)),
+            Err("the art has 12 lines, at most 10 fit".to_string())
+        );
+        assert!(Art::parse("\n\n").is_err());
+    }
+
+    #[test]
+    fn test_art_parse_frames() {
+        // When
+        let art = Art::parse("\x1b[32m(o)\n%% 300ms\n(-)\n-\n%%\n\n").unwrap();
+        // Then the frames have the same size, and the colors of a frame
+        // don't leak into the next one
+        let frames = art.frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].duration, time::Duration::from_millis(300));
+        assert_eq!(frames[1].duration, FRAME_DURATION);
+        assert_eq!(art.height(), 2);
+        assert_eq!(frames[0].lines[1].to_string(), "   ");
+        assert_eq!(frames[1].lines[1].to_string(), "-  ");
+        assert_eq!(frames[1].lines[0].spans[0].style, Style::new());
+    }
+
+    #[test]
+    fn test_art_parse_invalid_frames() {
+        assert_eq!(
+            Art::parse("(o)\n%% soon\n(-)\n"),
+            Err("invalid frame duration `soon`".to_string())
+        );
+        assert_eq!(
+            Art::parse("(o)\n%%\n%%\n(-)\n"),
+            Err("the art has an empty frame".to_string())
+        );
+    }
+
+    #[test]
+    fn test_animation_advance() {
+        // Given
+        let art = Art::parse("1\n%% 300\n2\n%% 100\n").unwrap();
+        let mut animation = Animation::default();
+        // When
+        animation.advance(&art, time::Duration::from_millis(200));
+        // Then
+        assert_eq!(animation.frame(&art).lines[0].to_string(), "1");
+        // When
+        animation.advance(&art, time::Duration::from_millis(150));
+        // Then
+        assert_eq!(animation.frame(&art).lines[0].to_string(), "2");
+        // When the last frame is over
+        animation.advance(&art, time::Duration::from_millis(100));
+        // Then the animation starts over
+        assert_eq!(animation.frame(&art).lines[0].to_string(), "1");
+    }
+
+    #[test]
+    fn test_builtin_art_frames() {
+        let pack = ArtPack::default();
+        for art in [&pack.work, &pack.break_time] {
+            assert!(art.frames().len() > 1);
+            assert_eq!(art.height(), HEIGHT);
+        }
+    }
+
+    #[test]
+    fn test_art_pack_load() {
+        // Given a pack with the art of the breaks only
+        let dir = std::env::temp_dir().join(format!("pomodoro-tui-art-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("break.txt"), "zzz\n").unwrap();
+        // When
+        let pack = ArtPack::load(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        // Then
+        assert_eq!(text(&pack.break_time), vec!["zzz"]);
+        assert_eq!(pack.work, ArtPack::default().work);
+    }
+
+    #[test]
+    fn test_art_pack_load_missing() {
+        assert!(ArtPack::load(Path::new("/nonexistent/pack")).is_err());
+    }
+}
@@ -31,3 +31,30 @@ pub fn sleeping_cat() -> Vec<String> {
         r#"                    '---''(_/--'  `-'\_)       "#.into(),
     ]
 }
+
+/// The computer, with a prompt blinking on its screen.
+pub fn computer_frames() -> Vec<Vec<String>> {
+    let prompt = |screen: &str| {
+        let mut lines = computer();
+        lines[3] = lines[3].replacen("||       ||", screen, 1);
+        lines
+    };
+    vec![prompt("||>_     ||"), prompt("||>      ||")]
+}
+
+/// The cat, with its ZZZ drifting up.
+pub fn sleeping_cat_frames() -> Vec<Vec<String>> {
+    // Rows and columns of the Zs in the drawing
+    let zs = [(4, 15, "ZZZ"), (5, 17, "ZZ"), (6, 19, "z"), (7, 20, "z")];
+    let drift = |rows_up: usize| {
+        let mut lines = sleeping_cat();
+        for (row, column, z) in zs {
+            lines[row].replace_range(column..column + z.len(), &" ".repeat(z.len()));
+        }
+        for (row, column, z) in zs {
+            lines[row - rows_up].replace_range(column..column + z.len(), z);
+        }
+        lines
+    };
+    vec![drift(0), drift(1), drift(2)]
+}
@@ -0,0 +1,239 @@
+use std::time;
+
+use crate::keys::Action;
+
+/// Something the user asks for, with a key or in the command palette.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Action(Action),
+    SetWork(time::Duration),
+    SetBreak(time::Duration),
+    SetLongBreak(time::Duration),
+    Task(Option<String>),
+    TagAdd(String),
+    TagRemove(String),
+    Profile(String),
+}
+
+/// Commands of the palette, as shown to the user.
+pub const USAGES: [&str; 15] = [
+    "start-pause",
+    "reset",
+    "skip",
+    "ambient",
+    "help",
+    "next-tab",
+    "quit",
+    "set work <duration>",
+    "set break <duration>",
+    "set long-break <duration>",
+    "task <name>",
+    "task clear",
+    "tag add <tag>",
+    "tag remove <tag>",
+    "profile <name>",
+];
+
+impl Command {
+    /// Parses a command typed in the palette, like `set work 40m` or
+    /// `tag add review`.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        let rest = |from: usize| {
+            let rest = words[from..].join(" ");
+            match rest.is_empty() {
+                true => Err(format!("`{}` needs a value", words[..from].join(" "))),
+                false => Ok(rest),
+            }
+        };
+        let action = |action| Ok(Command::Action(action));
+        match words.as_slice() {
+            [] => Err("no command".to_string()),
+            ["start-pause"] | ["start"] | ["pause"] => action(Action::StartPause),
+            ["reset"] => action(Action::Reset),
+            ["skip"] => action(Action::Skip),
+            ["ambient"] => action(Action::ToggleAmbient),
+            ["help"] => action(Action::Help),
+            ["next-tab"] => action(Action::NextTab),
+            ["quit"] => action(Action::Quit),
+            ["set", "work", duration] => parse_duration(duration).map(Command::SetWork),
+            ["set", "break", duration] => parse_duration(duration).map(Command::SetBreak),
+            ["set", "long-break", duration] => parse_duration(duration).map(Command::SetLongBreak),
+            ["set", ..] => Err("usage: set work|break|long-break <duration>".to_string()),
+            ["task", "clear"] => Ok(Command::Task(None)),
+            ["task", ..] => rest(1).map(|task| Command::Task(Some(task))),
+            ["tag", "add", ..] => rest(2).map(Command::TagAdd),
+            ["tag", "remove", ..] => rest(2).map(Command::TagRemove),
+            ["tag", ..] => Err("usage: tag add|remove <tag>".to_string()),
+            ["profile", ..] => rest(1).map(Command::Profile),
+            [name, ..] => Err(format!("unknown command `{}`", name)),
+        }
+    }
+}
+
+/// Parses a duration like `40m`, `90s`, `1h30m`, or `25` for minutes.
+pub fn parse_duration(duration: &str) -> Result<time::Duration, String> {
+    let invalid = || format!("invalid duration `{}`", duration);
+    let mut seconds = 0;
+    let mut number = String::new();
+    for c in duration.chars() {
+        let unit = match c {
+            '0'..='9' => {
+                number.push(c);
+                continue;
+            }
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        seconds = number
+            .parse::<u64>()
+            .ok()
+            .and_then(|number| number.checked_mul(unit))
+            .and_then(|number| number.checked_add(seconds))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+    // A number without unit is a number of minutes
+    if !number.is_empty() {
+        if seconds > 0 {
+            return Err(invalid());
+        }
+        seconds = number
+            .parse::<u64>()
+            .ok()
+            .and_then(|number| number.checked_mul(60))
+            .ok_or_else(invalid)?;
+    }
+    match seconds {
+        0 => Err(invalid()),
+        seconds => Ok(time::Duration::from_secs(seconds)),
+    }
+}
+
+/// Scores how well the query matches the text, when all its characters
+/// appear in the text in the same order. Consecutive characters and
+/// characters at the start of words score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let index = position + text[position..].iter().position(|&t| t == c)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 2;
+        }
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(index);
+        position = index + 1;
+    }
+    Some(score)
+}
+
+/// Usages of the palette matching the input, best matches first. The
+/// words of the input past those of the command, like its values, are
+/// ignored.
+pub fn suggestions(input: &str) -> Vec<&'static str> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let mut matches: Vec<(u32, usize, &str)> = USAGES
+        .iter()
+        .enumerate()
+        .filter_map(|(index, usage)| {
+            let name: Vec<&str> = usage
+                .split_whitespace()
+                .take_while(|word| !word.starts_with('<'))
+                .collect();
+            let takes_value = name.len() < usage.split_whitespace().count();
+            if words.len() > name.len() && !takes_value {
+                return None;
+            }
+            let query = words[..words.len().min(name.len())].join(" ");
+            fuzzy_score(&query, &name.join(" ")).map(|score| (score, index, *usage))
+        })
+        .collect();
+    matches.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    matches.into_iter().map(|(_, _, usage)| usage).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_parse() {
+        assert_eq!(
+            Command::parse("set work 40m"),
+            Ok(Command::SetWork(time::Duration::from_secs(40 * 60)))
+        );
+        assert_eq!(Command::parse(" skip "), Ok(Command::Action(Action::Skip)));
+        assert_eq!(
+            Command::parse("tag add code review"),
+            Ok(Command::TagAdd("code review".to_string()))
+        );
+        assert_eq!(
+            Command::parse("profile deep-work"),
+            Ok(Command::Profile("deep-work".to_string()))
+        );
+        assert_eq!(Command::parse("task clear"), Ok(Command::Task(None)));
+        assert_eq!(
+            Command::parse("tag add"),
+            Err("`tag add` needs a value".to_string())
+        );
+        assert_eq!(
+            Command::parse("lunch"),
+            Err("unknown command `lunch`".to_string())
+        );
+        assert!(Command::parse("set work forever").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("25"), Ok(time::Duration::from_secs(1500)));
+        assert_eq!(parse_duration("90s"), Ok(time::Duration::from_secs(90)));
+        assert_eq!(parse_duration("1h30m"), Ok(time::Duration::from_secs(5400)));
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("10m5").is_err());
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("0").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_overflow() {
+        let invalid = |duration| Err(format!("invalid duration `{}`", duration));
+        assert_eq!(
+            parse_duration("307445734561825861"),
+            invalid("307445734561825861")
+        );
+        assert_eq!(
+            parse_duration("5124095576030432h"),
+            invalid("5124095576030432h")
+        );
+        assert_eq!(
+            parse_duration("18446744073709551615s1s"),
+            invalid("18446744073709551615s1s")
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("sw", "set work <duration>").is_some());
+        assert_eq!(fuzzy_score("ws", "set work <duration>"), None);
+        // Characters at the start of words are better matches
+        assert!(fuzzy_score("sk", "skip") > fuzzy_score("sk", "task <name>"));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        assert_eq!(suggestions("skip")[0], "skip");
+        assert_eq!(suggestions("sb")[0], "set break <duration>");
+        assert_eq!(suggestions("tag add review"), vec!["tag add <tag>"]);
+        assert_eq!(suggestions("set work 40m")[0], "set work <duration>");
+        assert_eq!(suggestions("").len(), USAGES.len());
+        assert!(suggestions("zzz").is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::ambient::AmbientSettings;
+use crate::art::ArtSettings;
+use crate::hooks::Hooks;
+use crate::keys::Action;
+use crate::messages::MessageSettings;
+use crate::notifier::WebhookSettings;
+use crate::sounds::{AlarmSettings, Sounds};
+use crate::speech::SpeechSettings;
+use crate::theme::ThemeSettings;
+
+/// Settings read from the TOML configuration file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Minutes left in a phase when a warning is sent
+    pub warnings: Vec<u64>,
+    /// Minutes of the long breaks, and number of work sessions between two
+    /// of them
+    pub long_break: Option<u64>,
+    pub long_break_interval: Option<u32>,
+    /// Number of work sessions to complete
+    pub goal: Option<u32>,
+    pub sounds: Sounds,
+    pub alarm: AlarmSettings,
+    pub ambient: AmbientSettings,
+    pub art: ArtSettings,
+    pub speech: SpeechSettings,
+    pub messages: MessageSettings,
+    /// Keys of the actions, in place of their default keys
+    pub keys: HashMap<Action, Vec<String>>,
+    /// Name of the theme, built in or from `themes`
+    pub theme: Option<String>,
+    pub themes: BTreeMap<String, ThemeSettings>,
+    pub hooks: Hooks,
+    pub webhooks: WebhookSettings,
+    /// Named sets of durations, applied with `--profile` or the command
+    /// palette
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// Durations and goal of a `[profiles.<name>]` configuration section. Those
+/// it doesn't set are left as they are.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    /// Minutes of the work sessions
+    pub work: Option<u64>,
+    /// Minutes of the breaks
+    #[serde(rename = "break")]
+    pub break_time: Option<u64>,
+    pub long_break: Option<u64>,
+    pub long_break_interval: Option<u32>,
+    pub goal: Option<u32>,
+}
+
+impl Config {
+    /// Default location of the configuration file, in the user
+    /// configuration directory.
+    pub fn default_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("pomodoro-tui")
+            .join("config.toml")
+    }
+
+    /// Loads the configuration file at the given path. A missing file is the
+    /// default configuration.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => Config::parse(&content).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid configuration in {}: {}", path.display(), err),
+                )
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_load_missing_file() {
+        let config = Config::load(Path::new("/nonexistent/config.toml")).unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_config_parse_hooks() {
+        // When
+        let config = Config::parse(
+            r#"
+            [hooks]
+            on_work_start = "slack-status focus"
+            timeout = 3
+            "#,
+        )
+        .unwrap();
+        // Then
+        assert_eq!(
+            config.hooks.on_work_start.as_deref(),
+            Some("slack-status focus")
+        );
+        assert_eq!(config.hooks.on_break_start, None);
+        assert_eq!(config.hooks.timeout, 3);
+        assert_eq!(config.hooks.log_file, Hooks::default().log_file);
+    }
+
+    #[test]
+    fn test_config_parse_webhooks() {
+        // When
+        let config = Config::parse(
+            r#"
+            [webhooks]
+            urls = ["http://localhost:8080/focus"]
+            retries = 1
+            "#,
+        )
+        .unwrap();
+        // Then
+        assert_eq!(config.webhooks.urls, vec!["http://localhost:8080/focus"]);
+        assert_eq!(config.webhooks.retries, 1);
+        assert_eq!(config.webhooks.timeout, WebhookSettings::default().timeout);
+    }
+
+    #[test]
+    fn test_config_parse_warnings() {
+        let config = Config::parse("warnings = [5, 1]\n").unwrap();
+        assert_eq!(config.warnings, vec![5, 1]);
+    }
+
+    #[test]
+    fn test_config_parse_sounds() {
+        // When
+        let config = Config::parse(
+            r#"
+            long_break = 15
+            goal = 8
+
+            [sounds]
+            break_start = "/usr/share/sounds/bell.ogg"
+            volume = 40
+            "#,
+        )
+        .unwrap();
+        // Then
+        assert_eq!(config.long_break, Some(15));
+        assert_eq!(config.long_break_interval, None);
+        assert_eq!(config.goal, Some(8));
+        assert_eq!(
+            config.sounds.break_start,
+            Some(PathBuf::from("/usr/share/sounds/bell.ogg"))
+        );
+        assert_eq!(config.sounds.volume, 40);
+        assert_eq!(config.sounds.max_duration, None);
+    }
+
+    #[test]
+    fn test_config_parse_keys() {
+        let config = Config::parse("[keys]\nstart_pause = [\"space\"]\n").unwrap();
+        assert_eq!(
+            config.keys,
+            HashMap::from([(Action::StartPause, vec!["space".to_string()])])
+        );
+        assert!(Config::parse("[keys]\nlunch = [\"l\"]\n").is_err());
+    }
+
+    #[test]
+    fn test_config_parse_profiles() {
+        // When
+        let config = Config::parse(
+            r#"
+            [profiles.deep-work]
+            work = 50
+            break = 10
+            "#,
+        )
+        .unwrap();
+        // Then
+        assert_eq!(
+            config.profiles["deep-work"],
+            Profile {
+                work: Some(50),
+                break_time: Some(10),
+                ..Default::default()
+            }
+        );
+        assert!(Config::parse("[profiles.nap]\nsleep = 20\n").is_err());
+    }
+
+    #[test]
+    fn test_config_parse_themes() {
+        // When
+        let config = Config::parse(
+            r#"
+            theme = "mine"
+
+            [themes.mine]
+            base = "gruvbox"
+            work = "light-red"
+            "#,
+        )
+        .unwrap();
+        // Then
+        assert_eq!(config.theme.as_deref(), Some("mine"));
+        assert_eq!(config.themes["mine"].base.as_deref(), Some("gruvbox"));
+        assert_eq!(
+            config.themes["mine"].work,
+            Some(ratatui::style::Color::LightRed)
+        );
+    }
+
+    #[test]
+    fn test_config_parse_art() {
+        let config = Config::parse("[art]\npack = \"cats\"\nbreak = \"/tmp/nap.txt\"\n").unwrap();
+        assert_eq!(config.art.pack.as_deref(), Some("cats"));
+        assert_eq!(config.art.work, None);
+        assert_eq!(config.art.break_time, Some(PathBuf::from("/tmp/nap.txt")));
+        assert!(config.art.animate);
+        let config = Config::parse("[art]\nanimate = false\n").unwrap();
+        assert!(!config.art.animate);
+    }
+
+    #[test]
+    fn test_config_parse_unknown_field() {
+        assert!(Config::parse("[hooks]\non_lunch = \"eat\"\n").is_err());
+    }
+}
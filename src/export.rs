@@ -0,0 +1,256 @@
+use std::io;
+use std::io::Write;
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+
+use crate::history::Session;
+use crate::PomodoroState;
+
+/// Header of the CSV export, also the default column mapping of the import.
+pub const CSV_HEADER: [&str; 7] = [
+    "start",
+    "end",
+    "phase",
+    "duration_seconds",
+    "task",
+    "tags",
+    "notes",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Csv,
+    Json,
+    Ics,
+}
+
+/// Sessions started between `since` and `until`, both included.
+pub fn filter_by_date(
+    sessions: &[Session],
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+) -> Vec<&Session> {
+    sessions
+        .iter()
+        .filter(|session| {
+            let day = session.start.date_naive();
+            since.is_none_or(|since| day >= since) && until.is_none_or(|until| day <= until)
+        })
+        .collect()
+}
+
+pub fn export(sessions: &[&Session], format: Format, writer: &mut impl Write) -> io::Result<()> {
+    match format {
+        Format::Csv => export_csv(sessions, writer),
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *writer, sessions)?;
+            writeln!(writer)
+        }
+        Format::Ics => export_ics(sessions, Utc::now(), writer),
+    }
+}
+
+fn session_end(session: &Session) -> DateTime<Utc> {
+    session.start.to_utc() + TimeDelta::seconds(session.duration as i64)
+}
+
+fn export_csv(sessions: &[&Session], writer: &mut impl Write) -> io::Result<()> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    csv_writer.write_record(CSV_HEADER)?;
+    for session in sessions {
+        csv_writer.write_record([
+            session.start.to_rfc3339(),
+            session_end(session)
+                .with_timezone(&session.start.timezone())
+                .to_rfc3339(),
+            session.phase.name().to_string(),
+            session.duration.to_string(),
+            session.task.clone().unwrap_or_default(),
+            session.tags.join(";"),
+            session.notes.clone(),
+        ])?;
+    }
+    csv_writer.flush()
+}
+
+/// Writes the sessions as events created at the given time.
+fn export_ics(
+    sessions: &[&Session],
+    created: DateTime<Utc>,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//pomodoro-tui//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    let stamp = format_ics_date(created);
+    for session in sessions {
+        let start = format_ics_date(session.start.to_utc());
+        let end = format_ics_date(session_end(session));
+        let summary = match &session.task {
+            Some(task) => task.clone(),
+            None => match session.phase {
+                PomodoroState::Work => "Work".to_string(),
+                PomodoroState::Break => "Break".to_string(),
+            },
+        };
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!(
+            "UID:{}-{}@pomodoro-tui",
+            start,
+            session.phase.name()
+        ));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART:{}", start));
+        lines.push(format!("DTEND:{}", end));
+        lines.push(format!("SUMMARY:{}", escape_ics_text(&summary)));
+        if !session.notes.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape_ics_text(&session.notes)));
+        }
+        let categories: Vec<String> = std::iter::once(session.phase.name().to_string())
+            .chain(session.tags.iter().map(|tag| escape_ics_text(tag)))
+            .collect();
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    for line in lines {
+        write!(writer, "{}\r\n", fold_ics_line(&line))?;
+    }
+    Ok(())
+}
+
+fn format_ics_date(date: DateTime<Utc>) -> String {
+    date.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+/// Folds a content line so that no line is longer than 75 octets, as
+/// required by RFC 5545.
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_length = 0;
+    for c in line.chars() {
+        if line_length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(c);
+        line_length += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, TimeZone};
+
+    fn session(day: u32, task: Option<&str>, notes: &str) -> Session {
+        Session {
+            start: FixedOffset::east_opt(3600)
+                .unwrap()
+                .with_ymd_and_hms(2025, 3, day, 9, 30, 0)
+                .unwrap()
+                .into(),
+            phase: PomodoroState::Work,
+            duration: 25 * 60,
+            task: task.map(String::from),
+            tags: vec!["rust".to_string(), "docs".to_string()],
+            notes: notes.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_filter_by_date() {
+        let sessions = vec![
+            session(1, None, ""),
+            session(2, None, ""),
+            session(3, None, ""),
+        ];
+        let since = NaiveDate::from_ymd_opt(2025, 3, 2);
+        let until = NaiveDate::from_ymd_opt(2025, 3, 2);
+        assert_eq!(filter_by_date(&sessions, since, None).len(), 2);
+        assert_eq!(filter_by_date(&sessions, None, until).len(), 2);
+        assert_eq!(filter_by_date(&sessions, since, until), vec![&sessions[1]]);
+    }
+
+    #[test]
+    fn test_export_csv() {
+        // Given
+        let session = session(14, Some("write, test"), "");
+        let mut output = Vec::new();
+        // When
+        export(&[&session], Format::Csv, &mut output).unwrap();
+        // Then
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(lines.next().unwrap(), CSV_HEADER.join(","));
+        let expected_start = session.start.to_rfc3339();
+        let expected_end = (session.start + TimeDelta::minutes(25)).to_rfc3339();
+        assert_eq!(
+            lines.next().unwrap(),
+            format!(
+                "{},{},work,1500,\"write, test\",rust;docs,",
+                expected_start, expected_end
+            )
+        );
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn test_export_json() {
+        // Given
+        let session = session(14, Some("write"), "notes");
+        let mut output = Vec::new();
+        // When
+        export(&[&session], Format::Json, &mut output).unwrap();
+        // Then
+        let exported: Vec<Session> = serde_json::from_slice(&output).unwrap();
+        assert_eq!(exported, vec![session]);
+    }
+
+    #[test]
+    fn test_export_ics() {
+        // Given
+        let with_task = session(14, Some("write; test"), "first line\r\nsecond\rthird");
+        let without_task = session(15, None, "");
+        let created = Utc.with_ymd_and_hms(2025, 3, 20, 18, 0, 0).unwrap();
+        let mut output = Vec::new();
+        // When
+        export_ics(&[&with_task, &without_task], created, &mut output).unwrap();
+        // Then
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(output.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(output.matches("BEGIN:VEVENT\r\n").count(), 2);
+        assert!(output.contains("DTSTART:20250314T083000Z\r\n"));
+        assert!(output.contains("DTEND:20250314T085500Z\r\n"));
+        assert_eq!(output.matches("DTSTAMP:20250320T180000Z\r\n").count(), 2);
+        assert!(output.contains("SUMMARY:write\\; test\r\n"));
+        assert!(output.contains("DESCRIPTION:first line\\nsecond\\nthird\r\n"));
+        assert!(output.contains("SUMMARY:Work\r\n"));
+        assert!(output.contains("CATEGORIES:work,rust,docs\r\n"));
+    }
+
+    #[test]
+    fn test_fold_ics_line() {
+        let line = format!("DESCRIPTION:{}", "a".repeat(100));
+        let folded = fold_ics_line(&line);
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 75);
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
@@ -0,0 +1,278 @@
+use std::fs;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::PomodoroState;
+
+/// A completed work or break interval, as stored in the history file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub start: DateTime<Local>,
+    pub phase: PomodoroState,
+    /// Duration of the interval, in seconds
+    pub duration: u64,
+    #[serde(default)]
+    pub task: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub notes: String,
+}
+
+impl Session {
+    /// Returns whether the query, matched case-insensitively, appears in the
+    /// date, phase, task, tags or notes of the session.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        let phase = self.phase.name();
+        self.start
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+            .contains(&query)
+            || phase.contains(&query)
+            || self
+                .task
+                .as_ref()
+                .is_some_and(|task| task.to_lowercase().contains(&query))
+            || self
+                .tags
+                .iter()
+                .any(|tag| tag.to_lowercase().contains(&query))
+            || self.notes.to_lowercase().contains(&query)
+    }
+}
+
+/// Persistent history of sessions, stored as one JSON object per line.
+pub struct History {
+    path: PathBuf,
+    sessions: Vec<Session>,
+    /// Number of invalid lines skipped when loading
+    skipped: usize,
+}
+
+impl History {
+    /// Loads the history stored at the given path. A missing file is an
+    /// empty history, and the lines that aren't sessions are skipped.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut sessions = Vec::new();
+        let mut skipped = 0;
+        match fs::File::open(path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    let line = line?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str(&line) {
+                        Ok(session) => sessions.push(session),
+                        Err(_) => skipped += 1,
+                    }
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => return Err(err),
+        }
+        Ok(History {
+            path: path.to_path_buf(),
+            sessions,
+            skipped,
+        })
+    }
+
+    /// Default location of the history file, in the user data directory.
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("pomodoro-tui")
+            .join("history.jsonl")
+    }
+
+    /// Number of lines of the file that couldn't be loaded. They are lost
+    /// the next time the whole history is saved, after an edit.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    pub fn sessions(&self) -> &[Session] {
+        &self.sessions
+    }
+
+    /// Indexes of the sessions matching the query, see `Session::matches`.
+    pub fn search(&self, query: &str) -> Vec<usize> {
+        self.sessions
+            .iter()
+            .enumerate()
+            .filter(|(_, session)| session.matches(query))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn add(&mut self, session: Session) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&session)?)?;
+        self.sessions.push(session);
+        Ok(())
+    }
+
+    /// Adds several sessions at once, keeping the history sorted by start.
+    pub fn add_all(&mut self, sessions: Vec<Session>) -> io::Result<()> {
+        let previous = self.sessions.clone();
+        self.sessions.extend(sessions);
+        self.sessions.sort_by_key(|session| session.start);
+        self.save().inspect_err(|_| self.sessions = previous)
+    }
+
+    pub fn update(&mut self, index: usize, session: Session) -> io::Result<()> {
+        let previous = std::mem::replace(&mut self.sessions[index], session);
+        self.save().inspect_err(|_| self.sessions[index] = previous)
+    }
+
+    pub fn remove(&mut self, index: usize) -> io::Result<Session> {
+        let session = self.sessions.remove(index);
+        match self.save() {
+            Ok(()) => Ok(session),
+            Err(err) => {
+                self.sessions.insert(index, session);
+                Err(err)
+            }
+        }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write to a temporary file first, so that a failure does not
+        // leave a truncated history behind
+        let tmp_path = self.path.with_extension("jsonl.tmp");
+        let mut file = io::BufWriter::new(fs::File::create(&tmp_path)?);
+        for session in &self.sessions {
+            writeln!(file, "{}", serde_json::to_string(session)?)?;
+        }
+        file.flush()?;
+        drop(file);
+        fs::rename(tmp_path, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn history_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "pomodoro-tui-history-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("history.jsonl")
+    }
+
+    fn session(task: &str) -> Session {
+        Session {
+            start: Local.with_ymd_and_hms(2025, 3, 14, 9, 30, 0).unwrap(),
+            phase: PomodoroState::Work,
+            duration: 25 * 60,
+            task: Some(task.to_string()),
+            tags: vec!["rust".to_string()],
+            notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_history_load_missing_file() {
+        let history = History::load(&history_path("missing")).unwrap();
+        assert!(history.sessions().is_empty());
+    }
+
+    #[test]
+    fn test_history_add_and_reload() {
+        // Given
+        let path = history_path("add");
+        let mut history = History::load(&path).unwrap();
+        // When
+        history.add(session("write tests")).unwrap();
+        history.add(session("review")).unwrap();
+        // Then
+        let reloaded = History::load(&path).unwrap();
+        assert_eq!(reloaded.sessions(), history.sessions());
+        assert_eq!(reloaded.sessions().len(), 2);
+    }
+
+    #[test]
+    fn test_history_update_and_remove() {
+        // Given
+        let path = history_path("edit");
+        let mut history = History::load(&path).unwrap();
+        history.add(session("write tests")).unwrap();
+        history.add(session("review")).unwrap();
+        // When
+        let mut edited = session("code review");
+        edited.notes = "took longer than expected".to_string();
+        history.update(1, edited.clone()).unwrap();
+        let removed = history.remove(0).unwrap();
+        // Then
+        assert_eq!(removed.task.as_deref(), Some("write tests"));
+        let reloaded = History::load(&path).unwrap();
+        assert_eq!(reloaded.sessions(), &[edited]);
+    }
+
+    #[test]
+    fn test_history_add_all_sorts_sessions() {
+        // Given
+        let path = history_path("add_all");
+        let mut history = History::load(&path).unwrap();
+        history.add(session("today")).unwrap();
+        let mut older = session("last week");
+        older.start = Local.with_ymd_and_hms(2025, 3, 7, 9, 30, 0).unwrap();
+        // When
+        history.add_all(vec![older.clone()]).unwrap();
+        // Then
+        let reloaded = History::load(&path).unwrap();
+        assert_eq!(reloaded.sessions(), &[older, session("today")]);
+    }
+
+    #[test]
+    fn test_history_search() {
+        // Given
+        let path = history_path("search");
+        let mut history = History::load(&path).unwrap();
+        history.add(session("Write tests")).unwrap();
+        let mut other = session("review");
+        other.phase = PomodoroState::Break;
+        other.tags = vec!["Meetings".to_string()];
+        history.add(other).unwrap();
+        // Then
+        assert_eq!(history.search("write"), vec![0]);
+        assert_eq!(history.search("meetings"), vec![1]);
+        assert_eq!(history.search("break"), vec![1]);
+        assert_eq!(history.search("2025-03-14"), vec![0, 1]);
+        assert!(history.search("nothing").is_empty());
+    }
+
+    #[test]
+    fn test_history_load_invalid_data() {
+        // Given a history with a corrupt line between two sessions
+        let path = history_path("invalid");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let line = serde_json::to_string(&session("write tests")).unwrap();
+        fs::write(&path, format!("{}\nnot json\n{{\"start\n{}\n", line, line)).unwrap();
+        // When
+        let history = History::load(&path).unwrap();
+        // Then the sessions are loaded, and the invalid lines counted
+        assert_eq!(history.sessions().len(), 2);
+        assert_eq!(history.skipped(), 2);
+    }
+}
@@ -0,0 +1,332 @@
+use crossterm::event;
+use pomodoro_tui::history::{History, Session};
+use pomodoro_tui::theme::Theme;
+use ratatui::{
+    layout,
+    style::{self, Stylize},
+    text, widgets, Frame,
+};
+
+enum Mode {
+    Browse,
+    Search,
+    Edit(Field),
+    ConfirmDelete,
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Task,
+    Tags,
+    Notes,
+    Duration,
+}
+
+impl Field {
+    fn name(&self) -> &'static str {
+        match self {
+            Field::Task => "Task",
+            Field::Tags => "Tags",
+            Field::Notes => "Notes",
+            Field::Duration => "Duration (min)",
+        }
+    }
+
+    fn value(&self, session: &Session) -> String {
+        match self {
+            Field::Task => session.task.clone().unwrap_or_default(),
+            Field::Tags => session.tags.join(", "),
+            Field::Notes => session.notes.clone(),
+            Field::Duration => (session.duration / 60).to_string(),
+        }
+    }
+
+    fn apply(&self, session: &mut Session, input: &str) -> Result<(), String> {
+        let input = input.trim();
+        match self {
+            Field::Task => {
+                session.task = (!input.is_empty()).then(|| input.to_string());
+            }
+            Field::Tags => {
+                session.tags = input
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(String::from)
+                    .collect();
+            }
+            Field::Notes => session.notes = input.to_string(),
+            Field::Duration => {
+                session.duration = input
+                    .parse::<u64>()
+                    .ok()
+                    .and_then(|minutes| minutes.checked_mul(60))
+                    .ok_or_else(|| format!("Invalid duration: {}", input))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Screen listing the past sessions stored in the history.
+pub struct HistoryTab {
+    table_state: widgets::TableState,
+    query: String,
+    mode: Mode,
+    input: String,
+    status: Option<String>,
+}
+
+impl HistoryTab {
+    pub fn new() -> Self {
+        HistoryTab {
+            table_state: widgets::TableState::default().with_selected(0),
+            query: String::new(),
+            mode: Mode::Browse,
+            input: String::new(),
+            status: None,
+        }
+    }
+
+    /// Whether the tab is capturing text input, in which case every key
+    /// should be sent to it.
+    pub fn is_capturing_input(&self) -> bool {
+        !matches!(self.mode, Mode::Browse)
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some(status);
+    }
+
+    /// Indexes in the history of the sessions to display, newest first.
+    fn visible(&self, history: &History) -> Vec<usize> {
+        let mut indexes = history.search(&self.query);
+        indexes.reverse();
+        indexes
+    }
+
+    fn selected(&self, history: &History) -> Option<usize> {
+        let visible = self.visible(history);
+        self.table_state
+            .selected()
+            .and_then(|row| visible.get(row).copied())
+    }
+
+    pub fn select_next(&mut self, history: &History) {
+        let count = self.visible(history).len();
+        let selected = self.table_state.selected().unwrap_or(0);
+        self.table_state
+            .select(Some((selected + 1).min(count.saturating_sub(1))));
+    }
+
+    pub fn select_previous(&mut self) {
+        let selected = self.table_state.selected().unwrap_or(0);
+        self.table_state.select(Some(selected.saturating_sub(1)));
+    }
+
+    pub fn handle_key_event(&mut self, key_event: event::KeyEvent, history: &mut History) {
+        match self.mode {
+            Mode::Browse => self.handle_browse_key(key_event, history),
+            Mode::Search => match key_event.code {
+                event::KeyCode::Enter | event::KeyCode::Esc => {
+                    if key_event.code == event::KeyCode::Esc {
+                        self.query.clear();
+                    }
+                    self.mode = Mode::Browse;
+                }
+                event::KeyCode::Backspace => {
+                    self.query.pop();
+                    self.table_state.select(Some(0));
+                }
+                event::KeyCode::Char(c) => {
+                    self.query.push(c);
+                    self.table_state.select(Some(0));
+                }
+                _ => (),
+            },
+            Mode::Edit(field) => match key_event.code {
+                event::KeyCode::Enter => {
+                    self.mode = Mode::Browse;
+                    self.save_edit(field, history);
+                }
+                event::KeyCode::Esc => self.mode = Mode::Browse,
+                event::KeyCode::Backspace => {
+                    self.input.pop();
+                }
+                event::KeyCode::Char(c) => self.input.push(c),
+                _ => (),
+            },
+            Mode::ConfirmDelete => {
+                self.mode = Mode::Browse;
+                if let event::KeyCode::Char('y') = key_event.code {
+                    self.delete_selected(history);
+                }
+            }
+        }
+    }
+
+    fn handle_browse_key(&mut self, key_event: event::KeyEvent, history: &History) {
+        match key_event.code {
+            event::KeyCode::Down | event::KeyCode::Char('j') => self.select_next(history),
+            event::KeyCode::Up | event::KeyCode::Char('k') => self.select_previous(),
+            event::KeyCode::Char('/') => {
+                self.status = None;
+                self.mode = Mode::Search;
+            }
+            event::KeyCode::Char('c') => {
+                self.query.clear();
+                self.table_state.select(Some(0));
+            }
+            event::KeyCode::Char('d') if self.selected(history).is_some() => {
+                self.mode = Mode::ConfirmDelete;
+            }
+            event::KeyCode::Char(c @ ('t' | 'g' | 'n' | 'm')) => {
+                let field = match c {
+                    't' => Field::Task,
+                    'g' => Field::Tags,
+                    'n' => Field::Notes,
+                    _ => Field::Duration,
+                };
+                if let Some(index) = self.selected(history) {
+                    self.input = field.value(&history.sessions()[index]);
+                    self.status = None;
+                    self.mode = Mode::Edit(field);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn save_edit(&mut self, field: Field, history: &mut History) {
+        let Some(index) = self.selected(history) else {
+            return;
+        };
+        let mut session = history.sessions()[index].clone();
+        if let Err(err) = field.apply(&mut session, &self.input) {
+            self.status = Some(err);
+            return;
+        }
+        self.status = Some(match history.update(index, session) {
+            Ok(()) => format!("{} updated", field.name()),
+            Err(err) => format!("Failed to save history: {}", err),
+        });
+    }
+
+    fn delete_selected(&mut self, history: &mut History) {
+        let Some(index) = self.selected(history) else {
+            return;
+        };
+        self.status = Some(match history.remove(index) {
+            Ok(_) => "Session deleted".to_string(),
+            Err(err) => format!("Failed to save history: {}", err),
+        });
+        let count = self.visible(history).len();
+        let selected = self.table_state.selected().unwrap_or(0);
+        self.table_state
+            .select(Some(selected.min(count.saturating_sub(1))));
+    }
+
+    pub fn instructions(&self, theme: &Theme) -> text::Line<'_> {
+        match self.mode {
+            Mode::Browse => text::Line::from(vec![
+                " Search ".into(),
+                "</>".fg(theme.hint).bold(),
+                " Task ".into(),
+                "<T>".fg(theme.hint).bold(),
+                " Tags ".into(),
+                "<G>".fg(theme.hint).bold(),
+                " Notes ".into(),
+                "<N>".fg(theme.hint).bold(),
+                " Duration ".into(),
+                "<M>".fg(theme.hint).bold(),
+                " Delete ".into(),
+                "<D>".fg(theme.hint).bold(),
+            ]),
+            Mode::Search | Mode::Edit(_) => text::Line::from(vec![
+                " Confirm ".into(),
+                "<Enter>".fg(theme.hint).bold(),
+                " Cancel ".into(),
+                "<Esc> ".fg(theme.hint).bold(),
+            ]),
+            Mode::ConfirmDelete => text::Line::from(vec![
+                " Delete ".into(),
+                "<Y>".fg(theme.hint).bold(),
+                " Cancel ".into(),
+                "<Any key> ".fg(theme.hint).bold(),
+            ]),
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        frame: &mut Frame,
+        area: layout::Rect,
+        history: &History,
+        theme: &Theme,
+    ) {
+        let [table_area, status_area] =
+            layout::Layout::vertical([layout::Constraint::Fill(1), layout::Constraint::Length(1)])
+                .areas(area);
+
+        let sessions = history.sessions();
+        let rows: Vec<widgets::Row> = self
+            .visible(history)
+            .into_iter()
+            .map(|index| session_row(&sessions[index], theme))
+            .collect();
+        let header = widgets::Row::new(["Date", "Phase", "Duration", "Task", "Tags", "Notes"])
+            .style(style::Style::new().bold());
+        let table = widgets::Table::new(
+            rows,
+            [
+                layout::Constraint::Length(16),
+                layout::Constraint::Length(5),
+                layout::Constraint::Length(8),
+                layout::Constraint::Fill(1),
+                layout::Constraint::Fill(1),
+                layout::Constraint::Fill(2),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(style::Style::new().reversed());
+        frame.render_stateful_widget(table, table_area, &mut self.table_state);
+
+        let status = match self.mode {
+            Mode::Search => text::Line::from(vec!["Search: ".bold(), self.query.clone().into()]),
+            Mode::Edit(field) => text::Line::from(vec![
+                format!("{}: ", field.name()).bold(),
+                self.input.clone().into(),
+            ]),
+            Mode::ConfirmDelete => text::Line::from("Delete the selected session? (y/N)".bold()),
+            Mode::Browse => match &self.status {
+                Some(status) => text::Line::from(status.clone().italic()),
+                None if !self.query.is_empty() => text::Line::from(vec![
+                    "Filter: ".bold(),
+                    self.query.clone().into(),
+                    text::Span::styled(" (clear with <C>)", theme.dim_style()),
+                ]),
+                None => text::Line::default(),
+            },
+        };
+        frame.render_widget(widgets::Paragraph::new(status), status_area);
+    }
+}
+
+fn session_row<'a>(session: &'a Session, theme: &Theme) -> widgets::Row<'a> {
+    let phase = match session.phase {
+        pomodoro_tui::PomodoroState::Work => "Work".fg(theme.work),
+        pomodoro_tui::PomodoroState::Break => "Break".fg(theme.break_time),
+    };
+    widgets::Row::new(vec![
+        text::Text::from(session.start.format("%Y-%m-%d %H:%M").to_string()),
+        text::Text::from(phase),
+        text::Text::from(format!(
+            "{:02}:{:02}",
+            session.duration / 60,
+            session.duration % 60
+        )),
+        text::Text::from(session.task.clone().unwrap_or_default()),
+        text::Text::from(session.tags.join(", ")),
+        text::Text::from(session.notes.clone()),
+    ])
+}
@@ -0,0 +1,327 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::mpsc;
+use std::thread;
+use std::time;
+
+use chrono::Local;
+use serde::Deserialize;
+
+use crate::PomodoroState;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookEvent {
+    WorkStart,
+    BreakStart,
+    Pause,
+    Reset,
+    Complete,
+}
+
+impl HookEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::WorkStart => "on_work_start",
+            HookEvent::BreakStart => "on_break_start",
+            HookEvent::Pause => "on_pause",
+            HookEvent::Reset => "on_reset",
+            HookEvent::Complete => "on_complete",
+        }
+    }
+}
+
+/// State of the timer when a hook is fired, passed to the command in
+/// `POMO_*` environment variables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookContext {
+    pub phase: PomodoroState,
+    /// Duration of the phase, in seconds
+    pub duration: u64,
+    pub task: Option<String>,
+    /// Number of work sessions completed so far
+    pub session_count: u32,
+}
+
+/// Shell commands run on timer events.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    pub on_work_start: Option<String>,
+    pub on_break_start: Option<String>,
+    pub on_pause: Option<String>,
+    pub on_reset: Option<String>,
+    pub on_complete: Option<String>,
+    /// Seconds after which a command still running is killed
+    pub timeout: u64,
+    /// File where failing commands are logged
+    pub log_file: PathBuf,
+}
+
+impl Default for Hooks {
+    fn default() -> Self {
+        Hooks {
+            on_work_start: None,
+            on_break_start: None,
+            on_pause: None,
+            on_reset: None,
+            on_complete: None,
+            timeout: 10,
+            log_file: dirs::data_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("pomodoro-tui")
+                .join("hooks.log"),
+        }
+    }
+}
+
+impl Hooks {
+    fn command(&self, event: HookEvent) -> Option<&str> {
+        match event {
+            HookEvent::WorkStart => self.on_work_start.as_deref(),
+            HookEvent::BreakStart => self.on_break_start.as_deref(),
+            HookEvent::Pause => self.on_pause.as_deref(),
+            HookEvent::Reset => self.on_reset.as_deref(),
+            HookEvent::Complete => self.on_complete.as_deref(),
+        }
+    }
+
+    /// Command of the event, if there is one, with the context in its
+    /// environment.
+    fn job(&self, event: HookEvent, context: &HookContext) -> Option<Job> {
+        let command = self.command(event)?;
+        let mut cmd = process::Command::new("sh");
+        cmd.arg("-c")
+            .arg(command)
+            .env("POMO_EVENT", event.name())
+            .env("POMO_PHASE", context.phase.name())
+            .env("POMO_DURATION", context.duration.to_string())
+            .env("POMO_TASK", context.task.as_deref().unwrap_or(""))
+            .env("POMO_SESSION_COUNT", context.session_count.to_string())
+            .stdin(process::Stdio::null())
+            .stdout(process::Stdio::null())
+            .stderr(process::Stdio::null());
+        // In a group of its own, so that the processes started by the
+        // command are killed with it
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+
+        Some(Job {
+            cmd,
+            description: format!("{} `{}`", event.name(), command),
+            timeout: time::Duration::from_secs(self.timeout),
+            log_file: self.log_file.clone(),
+        })
+    }
+}
+
+/// Command of a hook, waiting to be run.
+struct Job {
+    cmd: process::Command,
+    /// Event and command, for the log
+    description: String,
+    timeout: time::Duration,
+    log_file: PathBuf,
+}
+
+/// Runs the commands of the hooks in a background thread, one after the
+/// other in the order of the events.
+#[derive(Default)]
+pub struct HookRunner {
+    hooks: Hooks,
+    /// Queue of the thread running the commands, started with the first one
+    jobs: Option<mpsc::Sender<Job>>,
+}
+
+impl HookRunner {
+    pub fn new(hooks: Hooks) -> Self {
+        HookRunner { hooks, jobs: None }
+    }
+
+    /// Queues the command of the event, if there is one. The command is
+    /// killed if it runs for longer than the timeout, and failures are
+    /// written to the log file.
+    pub fn run(&mut self, event: HookEvent, context: &HookContext) {
+        let Some(job) = self.hooks.job(event, context) else {
+            return;
+        };
+        let jobs = self.jobs.get_or_insert_with(|| {
+            let (tx, rx) = mpsc::channel::<Job>();
+            thread::spawn(move || {
+                for job in rx {
+                    if let Some(failure) = wait_for(job.cmd, job.timeout) {
+                        log_failure(&job.log_file, &format!("{} {}", job.description, failure));
+                    }
+                }
+            });
+            tx
+        });
+        let _ = jobs.send(job);
+    }
+}
+
+/// Runs the command until it exits or times out, and describes why it
+/// failed, if it did.
+fn wait_for(mut cmd: process::Command, timeout: time::Duration) -> Option<String> {
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(err) => return Some(format!("failed to start: {}", err)),
+    };
+    let deadline = time::Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return None,
+            Ok(Some(status)) => return Some(format!("failed with {}", status)),
+            Ok(None) if time::Instant::now() >= deadline => {
+                kill(&mut child);
+                let _ = child.wait();
+                return Some(format!("timed out after {}s", timeout.as_secs()));
+            }
+            Ok(None) => thread::sleep(time::Duration::from_millis(50)),
+            Err(err) => return Some(format!("failed: {}", err)),
+        }
+    }
+}
+
+/// Kills the process, and the processes of its group on Unix.
+fn kill(child: &mut process::Child) {
+    #[cfg(unix)]
+    if let Ok(pid) = libc::pid_t::try_from(child.id()) {
+        // SAFETY: killpg only sends a signal, the group being the one of
+        // the child which hasn't been waited for yet
+        if unsafe { libc::killpg(pid, libc::SIGKILL) } == 0 {
+            return;
+        }
+    }
+    let _ = child.kill();
+}
+
+fn log_failure(log_file: &Path, message: &str) {
+    if let Some(parent) = log_file.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(mut file) = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_file)
+    {
+        let _ = writeln!(file, "{} {}", Local::now().to_rfc3339(), message);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "pomodoro-tui-hooks-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    /// Waits for a file written in the background to have some content.
+    pub(crate) fn read_eventually(path: &Path) -> String {
+        for _ in 0..100 {
+            let content = fs::read_to_string(path).unwrap_or_default();
+            if content.ends_with('\n') {
+                return content;
+            }
+            thread::sleep(time::Duration::from_millis(20));
+        }
+        String::new()
+    }
+
+    fn context() -> HookContext {
+        HookContext {
+            phase: PomodoroState::Work,
+            duration: 1500,
+            task: Some("write tests".to_string()),
+            session_count: 3,
+        }
+    }
+
+    #[test]
+    fn test_hooks_run_with_environment() {
+        // Given
+        let output = temp_path("environment");
+        let hooks = Hooks {
+            on_work_start: Some(format!(
+                "echo \"$POMO_EVENT $POMO_PHASE $POMO_DURATION $POMO_TASK $POMO_SESSION_COUNT\" > {}",
+                output.display()
+            )),
+            ..Default::default()
+        };
+        // When
+        HookRunner::new(hooks).run(HookEvent::WorkStart, &context());
+        // Then
+        assert_eq!(
+            read_eventually(&output),
+            "on_work_start work 1500 write tests 3\n"
+        );
+    }
+
+    #[test]
+    fn test_hooks_log_failures() {
+        // Given
+        let log_file = temp_path("failure-log");
+        let hooks = Hooks {
+            on_pause: Some("exit 3".to_string()),
+            log_file: log_file.clone(),
+            ..Default::default()
+        };
+        // When
+        HookRunner::new(hooks).run(HookEvent::Pause, &context());
+        // Then
+        let log = read_eventually(&log_file);
+        assert!(log.contains("on_pause `exit 3` failed with exit status: 3"));
+    }
+
+    #[test]
+    fn test_hooks_run_in_order() {
+        // Given a first command slower than the second one
+        let output = temp_path("order");
+        let mut runner = HookRunner::new(Hooks {
+            on_complete: Some(format!("sleep 0.2; echo complete >> {}", output.display())),
+            on_break_start: Some(format!("echo break_start >> {}", output.display())),
+            ..Default::default()
+        });
+        // When
+        runner.run(HookEvent::Complete, &context());
+        runner.run(HookEvent::BreakStart, &context());
+        // Then
+        let _ = read_eventually(&output);
+        thread::sleep(time::Duration::from_millis(200));
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "complete\nbreak_start\n"
+        );
+    }
+
+    #[test]
+    fn test_hooks_timeout() {
+        // Given a command whose own child would write after the timeout
+        let output = temp_path("timeout");
+        let hooks = Hooks {
+            on_complete: Some(format!(
+                "(sleep 1; echo late > {}) & wait",
+                output.display()
+            )),
+            ..Default::default()
+        };
+        let job = hooks.job(HookEvent::Complete, &context()).unwrap();
+        // When
+        let start = time::Instant::now();
+        let failure = wait_for(job.cmd, time::Duration::from_millis(100));
+        // Then
+        assert_eq!(failure.as_deref(), Some("timed out after 0s"));
+        assert!(start.elapsed() < time::Duration::from_secs(1));
+        // The child was killed with the command
+        thread::sleep(time::Duration::from_millis(1500));
+        assert!(!output.exists());
+    }
+}
@@ -0,0 +1,485 @@
+use std::fmt;
+use std::io;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use serde::Deserialize;
+
+use crate::export::CSV_HEADER;
+use crate::history::Session;
+use crate::PomodoroState;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// Generic CSV file, read according to a `ColumnMapping`
+    Csv,
+    /// Output of `timew export`
+    Timewarrior,
+    /// JSON export of this tool
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DurationUnit {
+    Seconds,
+    Minutes,
+}
+
+/// Names of the CSV columns holding each field of a session. Only the start,
+/// and either the end or the duration, are required.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnMapping {
+    pub start: String,
+    pub end: Option<String>,
+    pub phase: Option<String>,
+    pub duration: Option<String>,
+    pub task: Option<String>,
+    pub tags: Option<String>,
+    pub notes: Option<String>,
+    pub duration_unit: DurationUnit,
+}
+
+impl Default for ColumnMapping {
+    /// Mapping matching the CSV export of this tool.
+    fn default() -> Self {
+        let column = |index: usize| Some(CSV_HEADER[index].to_string());
+        ColumnMapping {
+            start: CSV_HEADER[0].to_string(),
+            end: column(1),
+            phase: column(2),
+            duration: column(3),
+            task: column(4),
+            tags: column(5),
+            notes: column(6),
+            duration_unit: DurationUnit::Seconds,
+        }
+    }
+}
+
+impl ColumnMapping {
+    /// Overrides the default mapping with comma separated `field=column`
+    /// pairs, e.g. `start=Date,duration=Minutes`. An empty column name
+    /// ignores the field.
+    pub fn parse(mapping: &str) -> Result<Self, String> {
+        let mut columns = ColumnMapping::default();
+        for pair in mapping.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let (field, column) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected field=column, got '{}'", pair))?;
+            let column = column.trim().to_string();
+            let optional_column = (!column.is_empty()).then(|| column.clone());
+            match field.trim() {
+                "start" => columns.start = column,
+                "end" => columns.end = optional_column,
+                "phase" => columns.phase = optional_column,
+                "duration" => columns.duration = optional_column,
+                "task" => columns.task = optional_column,
+                "tags" => columns.tags = optional_column,
+                "notes" => columns.notes = optional_column,
+                field => return Err(format!("unknown field '{}'", field)),
+            }
+        }
+        Ok(columns)
+    }
+}
+
+/// An imported session overlapping a session already in the history, or
+/// another imported session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub session: Session,
+    pub existing: Session,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} min) overlaps {} ({} min)",
+            self.session.start.format("%Y-%m-%d %H:%M"),
+            self.session.duration / 60,
+            self.existing.start.format("%Y-%m-%d %H:%M"),
+            self.existing.duration / 60,
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Sessions to add to the history
+    pub sessions: Vec<Session>,
+    /// Number of sessions already in the history
+    pub duplicates: usize,
+    pub conflicts: Vec<Conflict>,
+    /// Records which could not be read
+    pub errors: Vec<String>,
+}
+
+/// Reads the sessions of an export, and sorts them against the sessions
+/// already in the history: exact duplicates are skipped, and overlapping
+/// sessions are reported as conflicts instead of being imported.
+pub fn import(
+    reader: impl io::Read,
+    format: Format,
+    mapping: &ColumnMapping,
+    existing: &[Session],
+) -> io::Result<ImportReport> {
+    let (sessions, errors) = match format {
+        Format::Csv => read_csv(reader, mapping)?,
+        Format::Timewarrior => read_timewarrior(reader)?,
+        Format::Json => (
+            serde_json::from_reader(reader)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+            Vec::new(),
+        ),
+    };
+
+    let mut report = ImportReport {
+        errors,
+        ..Default::default()
+    };
+    for session in sessions {
+        let known = existing.iter().chain(report.sessions.iter());
+        if let Some(other) = known
+            .clone()
+            .find(|other| other.start == session.start && other.duration == session.duration)
+        {
+            if other.phase == session.phase {
+                report.duplicates += 1;
+                continue;
+            }
+        }
+        match known.clone().find(|other| overlaps(&session, other)) {
+            Some(other) => report.conflicts.push(Conflict {
+                existing: other.clone(),
+                session,
+            }),
+            None => report.sessions.push(session),
+        }
+    }
+    Ok(report)
+}
+
+fn end(session: &Session) -> DateTime<Local> {
+    session.start + TimeDelta::seconds(session.duration as i64)
+}
+
+fn overlaps(session: &Session, other: &Session) -> bool {
+    session.start < end(other) && other.start < end(session)
+}
+
+fn read_csv(
+    reader: impl io::Read,
+    mapping: &ColumnMapping,
+) -> io::Result<(Vec<Session>, Vec<String>)> {
+    let mut csv_reader = csv::Reader::from_reader(reader);
+    let headers = csv_reader.headers()?.clone();
+    let index = |column: &Option<String>| -> io::Result<Option<usize>> {
+        match column {
+            Some(column) => headers
+                .iter()
+                .position(|header| header.trim() == column)
+                .map(Some)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("column '{}' not found in the CSV header", column),
+                    )
+                }),
+            None => Ok(None),
+        }
+    };
+    let start = index(&Some(mapping.start.clone()))?.unwrap();
+    // Columns of the default mapping are optional, since they may simply
+    // not be exported by the other tool
+    let optional = |column: &Option<String>, default: Option<&str>| {
+        if column.as_deref() == default {
+            Ok(column
+                .as_ref()
+                .and_then(|column| headers.iter().position(|header| header.trim() == column)))
+        } else {
+            index(column)
+        }
+    };
+    let defaults = ColumnMapping::default();
+    let end_index = optional(&mapping.end, defaults.end.as_deref())?;
+    let phase = optional(&mapping.phase, defaults.phase.as_deref())?;
+    let duration = optional(&mapping.duration, defaults.duration.as_deref())?;
+    let task = optional(&mapping.task, defaults.task.as_deref())?;
+    let tags = optional(&mapping.tags, defaults.tags.as_deref())?;
+    let notes = optional(&mapping.notes, defaults.notes.as_deref())?;
+    if end_index.is_none() && duration.is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "either an end or a duration column is required",
+        ));
+    }
+
+    let mut sessions = Vec::new();
+    let mut errors = Vec::new();
+    for (line, record) in csv_reader.records().enumerate() {
+        // The header is on the first line
+        let line = line + 2;
+        let record = match record {
+            Ok(record) => record,
+            Err(err) => {
+                errors.push(format!("line {}: {}", line, err));
+                continue;
+            }
+        };
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+
+        let Some(session_start) = field(Some(start)).and_then(parse_date_time) else {
+            errors.push(format!("line {}: invalid or missing start", line));
+            continue;
+        };
+        let session_duration = match (field(duration), field(end_index)) {
+            (Some(duration), _) => parse_duration(duration, mapping.duration_unit),
+            (None, Some(end)) => parse_date_time(end)
+                .map(|end| (end - session_start).num_seconds())
+                .and_then(|seconds| u64::try_from(seconds).ok()),
+            (None, None) => None,
+        };
+        let Some(session_duration) = session_duration else {
+            errors.push(format!("line {}: invalid or missing duration", line));
+            continue;
+        };
+        let Some(session_phase) = parse_phase(field(phase)) else {
+            errors.push(format!("line {}: unknown phase", line));
+            continue;
+        };
+
+        sessions.push(Session {
+            start: session_start,
+            phase: session_phase,
+            duration: session_duration,
+            task: field(task).map(String::from),
+            tags: field(tags).map(parse_tags).unwrap_or_default(),
+            notes: field(notes).unwrap_or_default().to_string(),
+        });
+    }
+    Ok((sessions, errors))
+}
+
+fn parse_date_time(date: &str) -> Option<DateTime<Local>> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+        return Some(date.with_timezone(&Local));
+    }
+    if let Ok(date) = NaiveDateTime::parse_from_str(date, "%Y%m%dT%H%M%SZ") {
+        return Some(Utc.from_utc_datetime(&date).with_timezone(&Local));
+    }
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(date, format).ok())
+        .and_then(|date| Local.from_local_datetime(&date).earliest())
+}
+
+fn parse_duration(duration: &str, unit: DurationUnit) -> Option<u64> {
+    let duration: f64 = duration.parse().ok().filter(|d: &f64| *d >= 0.0)?;
+    Some(match unit {
+        DurationUnit::Seconds => duration.round() as u64,
+        DurationUnit::Minutes => (duration * 60.0).round() as u64,
+    })
+}
+
+fn parse_phase(phase: Option<&str>) -> Option<PomodoroState> {
+    match phase.map(str::to_lowercase).as_deref() {
+        None | Some("work" | "focus" | "pomodoro") => Some(PomodoroState::Work),
+        Some("break" | "short break" | "long break" | "rest") => Some(PomodoroState::Break),
+        Some(_) => None,
+    }
+}
+
+fn parse_tags(tags: &str) -> Vec<String> {
+    tags.split([';', ','])
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct TimewarriorInterval {
+    start: String,
+    end: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    annotation: Option<String>,
+}
+
+fn read_timewarrior(reader: impl io::Read) -> io::Result<(Vec<Session>, Vec<String>)> {
+    let intervals: Vec<TimewarriorInterval> = serde_json::from_reader(reader)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let mut sessions = Vec::new();
+    let mut errors = Vec::new();
+    for (index, interval) in intervals.into_iter().enumerate() {
+        let Some(end) = interval.end.as_deref() else {
+            errors.push(format!("interval {}: still open, skipped", index + 1));
+            continue;
+        };
+        let (Some(start), Some(end)) = (parse_date_time(&interval.start), parse_date_time(end))
+        else {
+            errors.push(format!("interval {}: invalid date", index + 1));
+            continue;
+        };
+        let Ok(duration) = u64::try_from((end - start).num_seconds()) else {
+            errors.push(format!("interval {}: ends before it starts", index + 1));
+            continue;
+        };
+        sessions.push(Session {
+            start,
+            phase: PomodoroState::Work,
+            duration,
+            task: None,
+            tags: interval.tags,
+            notes: interval.annotation.unwrap_or_default(),
+        });
+    }
+    Ok((sessions, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(hour: u32, minute: u32, minutes: u64) -> Session {
+        Session {
+            start: Utc
+                .with_ymd_and_hms(2025, 3, 14, hour, minute, 0)
+                .unwrap()
+                .with_timezone(&Local),
+            phase: PomodoroState::Work,
+            duration: minutes * 60,
+            task: None,
+            tags: Vec::new(),
+            notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_column_mapping_parse() {
+        // When
+        let mapping = ColumnMapping::parse("start=Date, duration=Minutes,end=").unwrap();
+        // Then
+        assert_eq!(mapping.start, "Date");
+        assert_eq!(mapping.duration.as_deref(), Some("Minutes"));
+        assert_eq!(mapping.end, None);
+        assert_eq!(mapping.task, ColumnMapping::default().task);
+        assert!(ColumnMapping::parse("start").is_err());
+        assert!(ColumnMapping::parse("unknown=Column").is_err());
+    }
+
+    #[test]
+    fn test_import_csv_with_mapping() {
+        // Given
+        let csv = "Date,Minutes,Kind,Project,Labels\n\
+                   2025-03-14T09:00:00Z,25,Focus,Report,a;b\n\
+                   2025-03-14T09:25:00Z,5,Short Break,,\n\
+                   not a date,25,Focus,,\n";
+        let mut mapping =
+            ColumnMapping::parse("start=Date,duration=Minutes,phase=Kind,task=Project,tags=Labels")
+                .unwrap();
+        mapping.duration_unit = DurationUnit::Minutes;
+        // When
+        let report = import(csv.as_bytes(), Format::Csv, &mapping, &[]).unwrap();
+        // Then
+        assert_eq!(report.sessions.len(), 2);
+        assert_eq!(report.sessions[0].start, session(9, 0, 25).start);
+        assert_eq!(report.sessions[0].duration, 25 * 60);
+        assert_eq!(report.sessions[0].task.as_deref(), Some("Report"));
+        assert_eq!(report.sessions[0].tags, vec!["a", "b"]);
+        assert_eq!(report.sessions[1].phase, PomodoroState::Break);
+        assert_eq!(report.errors, vec!["line 4: invalid or missing start"]);
+    }
+
+    #[test]
+    fn test_import_csv_missing_column() {
+        let csv = "Date,Minutes\n2025-03-14T09:00:00Z,25\n";
+        let mapping = ColumnMapping::parse("start=Date,duration=Duration").unwrap();
+        let err = import(csv.as_bytes(), Format::Csv, &mapping, &[]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_import_own_csv_export() {
+        // Given
+        let sessions = vec![session(9, 0, 25), session(9, 30, 25)];
+        let mut csv = Vec::new();
+        crate::export::export(
+            &sessions.iter().collect::<Vec<_>>(),
+            crate::export::Format::Csv,
+            &mut csv,
+        )
+        .unwrap();
+        // When
+        let report = import(csv.as_slice(), Format::Csv, &ColumnMapping::default(), &[]).unwrap();
+        // Then
+        assert_eq!(report.sessions, sessions);
+    }
+
+    #[test]
+    fn test_import_timewarrior() {
+        // Given
+        let json = r#"[
+            {"id": 3, "start": "20250314T090000Z", "end": "20250314T092500Z", "tags": ["report"], "annotation": "draft"},
+            {"id": 2, "start": "20250314T100000Z", "end": "20250314T095000Z"},
+            {"id": 1, "start": "20250314T110000Z"}
+        ]"#;
+        // When
+        let report = import(
+            json.as_bytes(),
+            Format::Timewarrior,
+            &ColumnMapping::default(),
+            &[],
+        )
+        .unwrap();
+        // Then
+        let mut expected = session(9, 0, 25);
+        expected.tags = vec!["report".to_string()];
+        expected.notes = "draft".to_string();
+        assert_eq!(report.sessions, vec![expected]);
+        assert_eq!(report.errors.len(), 2);
+    }
+
+    #[test]
+    fn test_import_duplicates_and_conflicts() {
+        // Given
+        let existing = vec![session(9, 0, 25)];
+        let imported = vec![
+            session(9, 0, 25),
+            session(9, 10, 25),
+            session(10, 0, 25),
+            session(10, 0, 25),
+            session(10, 20, 25),
+        ];
+        let json = serde_json::to_string(&imported).unwrap();
+        // When
+        let report = import(
+            json.as_bytes(),
+            Format::Json,
+            &ColumnMapping::default(),
+            &existing,
+        )
+        .unwrap();
+        // Then
+        assert_eq!(report.sessions, vec![session(10, 0, 25)]);
+        assert_eq!(report.duplicates, 2);
+        assert_eq!(
+            report.conflicts,
+            vec![
+                Conflict {
+                    session: session(9, 10, 25),
+                    existing: session(9, 0, 25),
+                },
+                Conflict {
+                    session: session(10, 20, 25),
+                    existing: session(10, 0, 25),
+                },
+            ]
+        );
+    }
+}
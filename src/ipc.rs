@@ -0,0 +1,164 @@
+use std::fs;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+/// Command sent to a running timer by another process.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    /// Stop the alarm ringing until acknowledged
+    Ack,
+}
+
+impl Command {
+    fn name(&self) -> &'static str {
+        match self {
+            Command::Ack => "ack",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "ack" => Some(Command::Ack),
+            _ => None,
+        }
+    }
+}
+
+/// Default location of the socket of the running timer, in the user
+/// runtime directory.
+pub fn default_socket_path() -> PathBuf {
+    dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("pomodoro-tui.sock")
+}
+
+/// Listens on the socket in the background, and sends the commands received
+/// to the channel, one per line. Each connection is read in its own thread,
+/// so that a client which doesn't write doesn't hold back the others. The socket file is removed when the
+/// listener is dropped.
+pub struct Listener {
+    path: PathBuf,
+}
+
+impl Listener {
+    pub fn bind(path: &Path, commands: mpsc::Sender<Command>) -> io::Result<Self> {
+        // A socket left behind by a timer which didn't exit cleanly would
+        // prevent binding, but one still accepting connections belongs to
+        // another timer
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("another timer is listening on {}", path.display()),
+            ));
+        }
+        let _ = fs::remove_file(path);
+        let listener = UnixListener::bind(path)?;
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let commands = commands.clone();
+                thread::spawn(move || read_commands(stream, commands));
+            }
+        });
+        Ok(Listener {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+fn read_commands(stream: UnixStream, commands: mpsc::Sender<Command>) {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if let Some(command) = Command::parse(line.trim()) {
+            if commands.send(command).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Sends the command to the timer listening on the socket.
+pub fn send(path: &Path, command: Command) -> io::Result<()> {
+    let mut stream = UnixStream::connect(path)?;
+    writeln!(stream, "{}", command.name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time;
+
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "pomodoro-tui-ipc-{}-{}.sock",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_send_command() {
+        // Given
+        let path = socket_path("send");
+        let (tx, rx) = mpsc::channel();
+        let _listener = Listener::bind(&path, tx).unwrap();
+        // When
+        send(&path, Command::Ack).unwrap();
+        // Then
+        assert_eq!(
+            rx.recv_timeout(time::Duration::from_secs(5)).unwrap(),
+            Command::Ack
+        );
+    }
+
+    #[test]
+    fn test_silent_client() {
+        // Given a client connected without writing anything
+        let path = socket_path("silent");
+        let (tx, rx) = mpsc::channel();
+        let _listener = Listener::bind(&path, tx).unwrap();
+        let _silent = UnixStream::connect(&path).unwrap();
+        // When
+        send(&path, Command::Ack).unwrap();
+        // Then the command still gets through
+        assert_eq!(
+            rx.recv_timeout(time::Duration::from_secs(5)).unwrap(),
+            Command::Ack
+        );
+    }
+
+    #[test]
+    fn test_listener_removes_socket() {
+        // Given
+        let path = socket_path("drop");
+        let listener = Listener::bind(&path, mpsc::channel().0).unwrap();
+        assert!(path.exists());
+        // When
+        drop(listener);
+        // Then
+        assert!(!path.exists());
+        assert!(send(&path, Command::Ack).is_err());
+    }
+
+    #[test]
+    fn test_listener_already_running() {
+        // Given
+        let path = socket_path("running");
+        let _listener = Listener::bind(&path, mpsc::channel().0).unwrap();
+        // When
+        let err = Listener::bind(&path, mpsc::channel().0).err().unwrap();
+        // Then
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Something the user can do with a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    StartPause,
+    Reset,
+    Skip,
+    ToggleAmbient,
+    NextTab,
+    Help,
+    Palette,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::StartPause,
+        Action::Reset,
+        Action::Skip,
+        Action::ToggleAmbient,
+        Action::NextTab,
+        Action::Help,
+        Action::Palette,
+        Action::Quit,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::StartPause => "start_pause",
+            Action::Reset => "reset",
+            Action::Skip => "skip",
+            Action::ToggleAmbient => "toggle_ambient",
+            Action::NextTab => "next_tab",
+            Action::Help => "help",
+            Action::Palette => "palette",
+            Action::Quit => "quit",
+        }
+    }
+
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::StartPause => &["s"],
+            Action::Reset => &["r"],
+            Action::Skip => &["n"],
+            Action::ToggleAmbient => &["a"],
+            Action::NextTab => &["tab"],
+            Action::Help => &["?"],
+            Action::Palette => &[":"],
+            Action::Quit => &["q", "esc"],
+        }
+    }
+}
+
+/// A key, with the modifiers held while pressing it. Letters are case
+/// insensitive, so that Caps Lock doesn't get in the way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    fn new(code: KeyCode, mut modifiers: KeyModifiers) -> Self {
+        let code = match code {
+            KeyCode::Char(c) => {
+                // Shift is part of the character typed
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            // Shift+Tab is reported as a key of its own
+            KeyCode::BackTab => {
+                modifiers.insert(KeyModifiers::SHIFT);
+                KeyCode::Tab
+            }
+            code => code,
+        };
+        KeyChord { code, modifiers }
+    }
+
+    /// Parses a key like `s`, `esc`, `f1` or `ctrl+alt+s`.
+    pub fn parse(chord: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid key `{}`", chord);
+        let mut parts: Vec<&str> = chord.split('+').collect();
+        // `+` itself, alone or with modifiers
+        if chord.ends_with("++") || chord == "+" {
+            parts.pop();
+            *parts.last_mut().ok_or_else(invalid)? = "+";
+        }
+        let key = parts.pop().ok_or_else(invalid)?;
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in parts {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(invalid()),
+            };
+        }
+        let code = match key.to_lowercase().as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "enter" | "return" => KeyCode::Enter,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            name => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    (Some('f'), Some(_)) => KeyCode::F(name[1..].parse().map_err(|_| invalid())?),
+                    _ => return Err(invalid()),
+                }
+            }
+        };
+        Ok(KeyChord::new(code, modifiers))
+    }
+}
+
+impl From<&KeyEvent> for KeyChord {
+    fn from(event: &KeyEvent) -> Self {
+        KeyChord::new(event.code, event.modifiers)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            code => write!(f, "{}", code),
+        }
+    }
+}
+
+/// Keys of the actions, from the defaults and the `[keys]` configuration
+/// section.
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    actions: HashMap<KeyChord, Action>,
+    keys: HashMap<Action, Vec<KeyChord>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings::new(&HashMap::new()).expect("default key bindings are valid")
+    }
+}
+
+impl KeyBindings {
+    /// Binds the keys of the settings to their action, in place of its
+    /// default keys. A key bound to several actions is an error.
+    pub fn new(settings: &HashMap<Action, Vec<String>>) -> Result<Self, String> {
+        let mut bindings = KeyBindings {
+            actions: HashMap::new(),
+            keys: HashMap::new(),
+        };
+        for action in Action::ALL {
+            let keys: Vec<&str> = match settings.get(&action) {
+                Some(keys) => keys.iter().map(String::as_str).collect(),
+                None => action.default_keys().to_vec(),
+            };
+            for key in keys {
+                let chord = KeyChord::parse(key)?;
+                if let Some(other) = bindings.actions.insert(chord, action) {
+                    if other != action {
+                        return Err(format!(
+                            "key `{}` is bound to both {} and {}",
+                            key,
+                            other.name(),
+                            action.name()
+                        ));
+                    }
+                    continue;
+                }
+                bindings.keys.entry(action).or_default().push(chord);
+            }
+        }
+        Ok(bindings)
+    }
+
+    /// Action bound to the key pressed, if any.
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        self.actions.get(&KeyChord::from(event)).copied()
+    }
+
+    /// Keys bound to the action, like `Q/Esc`, for the help line.
+    pub fn hint(&self, action: Action) -> Option<String> {
+        let keys = self.keys.get(&action)?;
+        Some(
+            keys.iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("/"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_key_chord_parse() {
+        assert_eq!(
+            KeyChord::parse("ctrl+s").unwrap(),
+            KeyChord::new(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            KeyChord::parse("Ctrl+Alt+F5").unwrap(),
+            KeyChord::new(KeyCode::F(5), KeyModifiers::CONTROL | KeyModifiers::ALT)
+        );
+        assert_eq!(
+            KeyChord::parse("ctrl++").unwrap(),
+            KeyChord::new(KeyCode::Char('+'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            KeyChord::parse("space").unwrap(),
+            KeyChord::new(KeyCode::Char(' '), KeyModifiers::NONE)
+        );
+        assert!(KeyChord::parse("hyper+s").is_err());
+        assert!(KeyChord::parse("ss").is_err());
+        assert!(KeyChord::parse("").is_err());
+    }
+
+    #[test]
+    fn test_key_chord_display() {
+        assert_eq!(KeyChord::parse("ctrl+s").unwrap().to_string(), "Ctrl+S");
+        assert_eq!(KeyChord::parse("esc").unwrap().to_string(), "Esc");
+        assert_eq!(
+            KeyChord::parse("shift+tab").unwrap().to_string(),
+            "Shift+Tab"
+        );
+    }
+
+    #[test]
+    fn test_key_bindings_default() {
+        // Given
+        let bindings = KeyBindings::default();
+        // Then letters are case insensitive
+        assert_eq!(
+            bindings.action(&key(KeyCode::Char('s'), KeyModifiers::NONE)),
+            Some(Action::StartPause)
+        );
+        assert_eq!(
+            bindings.action(&key(KeyCode::Char('S'), KeyModifiers::SHIFT)),
+            Some(Action::StartPause)
+        );
+        assert_eq!(
+            bindings.action(&key(KeyCode::Char('s'), KeyModifiers::CONTROL)),
+            None
+        );
+        assert_eq!(bindings.hint(Action::Quit).as_deref(), Some("Q/Esc"));
+    }
+
+    #[test]
+    fn test_key_bindings_from_settings() {
+        // Given
+        let settings = HashMap::from([
+            (
+                Action::StartPause,
+                vec!["space".to_string(), "ctrl+s".to_string()],
+            ),
+            (Action::Quit, vec!["ctrl+q".to_string()]),
+        ]);
+        // When
+        let bindings = KeyBindings::new(&settings).unwrap();
+        // Then
+        assert_eq!(
+            bindings.action(&key(KeyCode::Char('s'), KeyModifiers::CONTROL)),
+            Some(Action::StartPause)
+        );
+        assert_eq!(
+            bindings.action(&key(KeyCode::Char('s'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            bindings.action(&key(KeyCode::Esc, KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            bindings.hint(Action::StartPause).as_deref(),
+            Some("Space/Ctrl+S")
+        );
+    }
+
+    #[test]
+    fn test_key_bindings_conflict() {
+        let settings = HashMap::from([(Action::Reset, vec!["S".to_string()])]);
+        assert_eq!(
+            KeyBindings::new(&settings).err().as_deref(),
+            Some("key `S` is bound to both start_pause and reset")
+        );
+    }
+}
@@ -1,18 +1,43 @@
+pub mod ambient;
+pub mod art;
+mod ascii_images;
+pub mod commands;
+pub mod config;
+pub mod export;
+pub mod history;
+pub mod hooks;
+pub mod import;
+#[cfg(unix)]
+pub mod ipc;
+pub mod keys;
+pub mod messages;
+pub mod notifier;
+pub mod sounds;
+pub mod speech;
+pub mod stats;
+pub mod theme;
+
 use std::fmt;
 use std::fs;
-use std::io::BufReader;
-use std::path::PathBuf;
-use std::process;
-use std::thread;
+use std::io::{BufReader, Cursor};
 use std::time;
 
-use notify_rust::Notification;
+use ambient::AmbientPlayer;
+use chrono::{DateTime, Local};
+use config::Profile;
+use hooks::{HookContext, HookEvent, HookRunner, Hooks};
+use messages::{Messages, Placeholders};
+use notifier::{Notification, Notifier};
 use rodio::Decoder;
 use rodio::OutputStream;
+use rodio::Sink;
 use rodio::Source;
+use serde::{Deserialize, Serialize};
+use sounds::{AlarmSettings, AudioPlayer, Player, Sound, SoundEvent, Sounds};
 
 struct Timer {
     duration: time::Duration,
+    default_duration: time::Duration,
     start_time: Option<time::Instant>,
     elapsed: time::Duration,
     is_running: bool,
@@ -23,6 +48,7 @@ impl Timer {
         let duration = time::Duration::from_secs(minutes * 60 + seconds);
         Timer {
             duration,
+            default_duration: duration,
             start_time: None,
             elapsed: time::Duration::from_secs(0),
             is_running: false,
@@ -40,11 +66,25 @@ impl Timer {
     }
 
     fn reset(&mut self) {
+        self.duration = self.default_duration;
         self.start_time = None;
         self.elapsed = time::Duration::from_secs(0);
         self.is_running = false;
     }
 
+    /// Resets the timer to run for the given duration once, instead of its
+    /// default duration.
+    fn reset_to(&mut self, duration: time::Duration) {
+        self.reset();
+        self.duration = duration;
+    }
+
+    /// Changes the default duration, and the one of the current run.
+    fn set_duration(&mut self, duration: time::Duration) {
+        self.default_duration = duration;
+        self.duration = duration;
+    }
+
     fn elapsed(&self) -> time::Duration {
         match self.start_time {
             Some(start_time) => self.elapsed + start_time.elapsed(),
@@ -58,6 +98,13 @@ impl Timer {
         }
         self.duration - self.elapsed()
     }
+
+    fn progress(&self) -> f64 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        (self.elapsed().as_secs_f64() / self.duration.as_secs_f64()).min(1.0)
+    }
 }
 
 impl fmt::Display for Timer {
@@ -68,33 +115,104 @@ impl fmt::Display for Timer {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PomodoroState {
     Work,
     Break,
 }
 
+impl PomodoroState {
+    /// Name of the phase in the history, exports, hooks and notifications.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PomodoroState::Work => "work",
+            PomodoroState::Break => "break",
+        }
+    }
+}
+
+/// Alarm ringing until acknowledged.
+struct PendingAlarm {
+    event: SoundEvent,
+    /// Sent again when no sound can be played
+    notification: Notification,
+    last_ring: time::Instant,
+    /// Number of times the alarm rang again
+    rings: u32,
+}
+
 pub struct Pomodoro {
     work_timer: Timer,
     break_timer: Timer,
     state: PomodoroState,
-    sound: PathBuf,
+    phase_start: Option<DateTime<Local>>,
+    task: Option<String>,
+    tags: Vec<String>,
+    completed_sessions: u32,
+    auto_start: bool,
+    /// Time left in a phase when a warning is sent, longest first
+    warnings: Vec<time::Duration>,
+    /// Number of warnings already sent in the current phase
+    warnings_sent: usize,
+    /// Duration of every few breaks, and the number of work sessions
+    /// between two of them
+    long_break: Option<time::Duration>,
+    long_break_interval: u32,
+    /// Whether the current phase is a long break
+    in_long_break: bool,
+    /// Number of work sessions to complete
+    goal: Option<u32>,
+    hooks: HookRunner,
+    messages: Messages,
+    /// Default sound, played when `sounds` has none for an event
+    sound: Sound,
+    sounds: Sounds,
+    /// Track looped while the work timer runs, if enabled
+    ambient: Option<AmbientPlayer>,
+    ambient_enabled: bool,
+    alarm: AlarmSettings,
+    /// Alarm of the last phase change, until the user acknowledges it
+    pending_alarm: Option<PendingAlarm>,
     no_sound: bool,
+    player: Box<dyn Player>,
+    notifier: Box<dyn Notifier>,
 }
 
 impl Pomodoro {
     pub fn new(
         work_time: (u64, u64),
         break_time: (u64, u64),
-        sound: PathBuf,
+        sound: Sound,
         no_sound: bool,
+        notifier: Box<dyn Notifier>,
     ) -> Self {
         Pomodoro {
             work_timer: Timer::new(work_time.0, work_time.1),
             break_timer: Timer::new(break_time.0, break_time.1),
             state: PomodoroState::Work,
+            phase_start: None,
+            task: None,
+            tags: Vec::new(),
+            completed_sessions: 0,
+            auto_start: true,
+            warnings: Vec::new(),
+            warnings_sent: 0,
+            long_break: None,
+            long_break_interval: 4,
+            in_long_break: false,
+            goal: None,
+            hooks: HookRunner::default(),
+            messages: Messages::default(),
             sound,
+            sounds: Sounds::default(),
+            ambient: None,
+            ambient_enabled: true,
+            alarm: AlarmSettings::default(),
+            pending_alarm: None,
             no_sound,
+            player: Box::new(AudioPlayer),
+            notifier,
         }
     }
 
@@ -106,6 +224,185 @@ impl Pomodoro {
         self.work_timer.to_string()
     }
 
+    pub fn break_progress(&self) -> f64 {
+        self.break_timer.progress()
+    }
+
+    pub fn work_progress(&self) -> f64 {
+        self.work_timer.progress()
+    }
+
+    pub fn task(&self) -> Option<&str> {
+        self.task.as_deref()
+    }
+
+    pub fn set_task(&mut self, task: Option<String>) {
+        self.task = task;
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+    }
+
+    /// Changes the duration of the work sessions, the current one included.
+    pub fn set_work_duration(&mut self, duration: time::Duration) {
+        self.work_timer.set_duration(duration);
+    }
+
+    /// Changes the duration of the breaks, the current one included unless
+    /// it is a long one.
+    pub fn set_break_duration(&mut self, duration: time::Duration) {
+        match self.in_long_break {
+            true => self.break_timer.default_duration = duration,
+            false => self.break_timer.set_duration(duration),
+        }
+    }
+
+    /// Changes the duration of the long breaks, the current one included,
+    /// keeping their interval.
+    pub fn set_long_break_duration(&mut self, duration: time::Duration) {
+        if self.in_long_break {
+            self.break_timer.duration = duration;
+        }
+        self.long_break = Some(duration);
+    }
+
+    /// Applies the settings of the profile, keeping the current ones it
+    /// doesn't set.
+    pub fn apply_profile(&mut self, profile: &Profile) {
+        let minutes = |minutes: u64| time::Duration::from_secs(minutes * 60);
+        if let Some(work) = profile.work {
+            self.set_work_duration(minutes(work));
+        }
+        if let Some(break_time) = profile.break_time {
+            self.set_break_duration(minutes(break_time));
+        }
+        if let Some(long_break) = profile.long_break {
+            self.set_long_break_duration(minutes(long_break));
+        }
+        if let Some(interval) = profile.long_break_interval {
+            self.long_break_interval = interval.max(1);
+        }
+        if profile.goal.is_some() {
+            self.goal = profile.goal;
+        }
+    }
+
+    /// Whether the next phase starts on its own when a phase is over.
+    pub fn set_auto_start(&mut self, auto_start: bool) {
+        self.auto_start = auto_start;
+    }
+
+    /// Sends a warning when the time left in a phase gets under each of the
+    /// given durations. Warnings longer than a phase are ignored.
+    pub fn set_warnings(&mut self, mut warnings: Vec<time::Duration>) {
+        warnings.sort_by(|a, b| b.cmp(a));
+        warnings.dedup();
+        self.warnings = warnings;
+        self.warnings_sent = 0;
+    }
+
+    /// Makes every `interval` break a long break, of the given duration.
+    pub fn set_long_break(&mut self, duration: time::Duration, interval: u32) {
+        self.long_break = Some(duration);
+        self.long_break_interval = interval.max(1);
+    }
+
+    /// Number of work sessions after which the goal reached sound is played.
+    pub fn set_goal(&mut self, goal: Option<u32>) {
+        self.goal = goal;
+    }
+
+    pub fn set_sounds(&mut self, sounds: Sounds) {
+        self.sounds = sounds;
+    }
+
+    pub fn set_alarm(&mut self, alarm: AlarmSettings) {
+        self.alarm = alarm;
+    }
+
+    /// Whether the alarm of the last phase change rings until acknowledged.
+    pub fn is_awaiting_ack(&self) -> bool {
+        self.pending_alarm.is_some()
+    }
+
+    /// Stops the alarm ringing until acknowledged.
+    pub fn acknowledge(&mut self) {
+        self.pending_alarm = None;
+    }
+
+    /// Plays the sounds with the given player, instead of the audio output.
+    pub fn set_player(&mut self, player: Box<dyn Player>) {
+        self.player = player;
+    }
+
+    pub fn set_ambient(&mut self, ambient: AmbientPlayer) {
+        self.ambient = Some(ambient);
+        self.update_ambient();
+    }
+
+    pub fn has_ambient(&self) -> bool {
+        self.ambient.is_some()
+    }
+
+    /// Turns the ambient track off, or back on.
+    pub fn toggle_ambient(&mut self) {
+        self.ambient_enabled = !self.ambient_enabled;
+        self.update_ambient();
+    }
+
+    pub fn is_ambient_playing(&self) -> bool {
+        self.ambient.as_ref().is_some_and(AmbientPlayer::is_playing)
+    }
+
+    /// Plays the ambient track while the work timer runs, and pauses it
+    /// otherwise.
+    fn update_ambient(&mut self) {
+        let play =
+            self.ambient_enabled && self.state == PomodoroState::Work && self.work_timer.is_running;
+        if let Some(ambient) = &mut self.ambient {
+            match play {
+                true => ambient.play(),
+                false => ambient.pause(),
+            }
+        }
+    }
+
+    pub fn set_messages(&mut self, messages: Messages) {
+        self.messages = messages;
+    }
+
+    /// Texts of the notifications and of the interface.
+    pub fn messages(&self) -> &Messages {
+        &self.messages
+    }
+
+    pub fn set_hooks(&mut self, hooks: Hooks) {
+        self.hooks = HookRunner::new(hooks);
+    }
+
+    /// Number of work sessions completed since the timer started.
+    pub fn completed_sessions(&self) -> u32 {
+        self.completed_sessions
+    }
+
+    /// Work session of the current or last phase, counted from the last long
+    /// break, and the number of work sessions between two long breaks.
+    pub fn cycle_position(&self) -> (u32, u32) {
+        let session = match self.state {
+            PomodoroState::Work => self.completed_sessions,
+            PomodoroState::Break => self.completed_sessions.saturating_sub(1),
+        };
+        (
+            session % self.long_break_interval + 1,
+            self.long_break_interval,
+        )
+    }
+
     pub fn state(&self) -> &PomodoroState {
         &self.state
     }
@@ -118,6 +415,17 @@ impl Pomodoro {
     }
 
     pub fn start_or_pause(&mut self) {
+        // The phase begins with its first start, a resume isn't a start
+        let event = if self.is_running() {
+            Some(HookEvent::Pause)
+        } else if self.phase_start.is_none() {
+            Some(self.start_event())
+        } else {
+            None
+        };
+        if self.phase_start.is_none() {
+            self.phase_start = Some(Local::now());
+        }
         match self.state {
             PomodoroState::Work => {
                 self.work_timer.start_or_pause();
@@ -126,94 +434,313 @@ impl Pomodoro {
                 self.break_timer.start_or_pause();
             }
         }
+        self.update_ambient();
+        if let Some(event) = event {
+            self.hooks.run(event, &self.hook_context());
+        }
     }
 
     pub fn reset(&mut self) {
         self.work_timer.reset();
         self.break_timer.reset();
         self.state = PomodoroState::Work;
+        self.in_long_break = false;
+        self.phase_start = None;
+        self.warnings_sent = 0;
+        self.pending_alarm = None;
+        self.update_ambient();
+        self.hooks.run(HookEvent::Reset, &self.hook_context());
+    }
+
+    /// Skips the current phase, and starts the next one.
+    pub fn skip(&mut self) {
+        self.start_other_phase(None);
+    }
+
+    /// Goes back to the other phase, running for the given duration. Used
+    /// to get a few more minutes of the phase which just ended.
+    pub fn snooze(&mut self, duration: time::Duration) {
+        self.start_other_phase(Some(duration));
     }
 
-    pub fn check_and_switch(&mut self) {
-        let (current_timer, next_timer, next_state, message) = match self.state {
+    fn start_other_phase(&mut self, duration: Option<time::Duration>) {
+        let (current_timer, other_timer, other_state) = match self.state {
             PomodoroState::Work => (
                 &mut self.work_timer,
                 &mut self.break_timer,
                 PomodoroState::Break,
-                "It's time to have a break.",
             ),
             PomodoroState::Break => (
                 &mut self.break_timer,
                 &mut self.work_timer,
                 PomodoroState::Work,
-                "It's time to research.",
             ),
         };
-
-        if current_timer.remaining() == time::Duration::from_secs(0) {
-            current_timer.reset();
-            next_timer.start_or_pause();
-            self.state = next_state;
-            show_notification("Pomodoro Timer", message, &self.sound, &self.no_sound);
+        current_timer.reset();
+        match duration {
+            Some(duration) => other_timer.reset_to(duration),
+            None => other_timer.reset(),
         }
+        other_timer.start_or_pause();
+        self.state = other_state;
+        self.in_long_break = false;
+        self.phase_start = Some(Local::now());
+        self.warnings_sent = 0;
+        self.pending_alarm = None;
+        self.update_ambient();
+        self.hooks.run(self.start_event(), &self.hook_context());
     }
-}
 
-pub fn sound_play(sound: &PathBuf) {
-    let (_stream, stream_handler) = match OutputStream::try_default() {
-        Ok(ok) => ok,
-        Err(_e) => return,
-    };
-    if let Ok(open_file) = fs::File::open(&sound) {
-        let file = BufReader::new(open_file);
-        if let Ok(sound_file) = Decoder::new(file) {
-            let _ = stream_handler.play_raw(sound_file.convert_samples());
-            std::thread::sleep(std::time::Duration::from_secs(3)); // Let it play
+    fn start_event(&self) -> HookEvent {
+        match self.state {
+            PomodoroState::Work => HookEvent::WorkStart,
+            PomodoroState::Break => HookEvent::BreakStart,
         }
     }
-}
 
-fn get_min_sec_from_duration(duration: time::Duration) -> (u64, u64) {
-    let total_seconds = duration.as_secs();
-    let minutes = total_seconds / 60;
-    let seconds = total_seconds % 60;
-    (minutes, seconds)
-}
+    fn hook_context(&self) -> HookContext {
+        let timer = match self.state {
+            PomodoroState::Work => &self.work_timer,
+            PomodoroState::Break => &self.break_timer,
+        };
+        HookContext {
+            phase: self.state,
+            duration: timer.duration.as_secs(),
+            task: self.task.clone(),
+            session_count: self.completed_sessions,
+        }
+    }
 
-fn show_notification(title: &str, message: &str, sound: &PathBuf, no_sound: &bool) {
-    if cfg!(target_os = "macos") {
-        let mut cmd = process::Command::new("osascript");
+    /// Switches to the next phase when the current one is over, and returns
+    /// the completed session to be recorded in the history.
+    pub fn check_and_switch(&mut self) -> Option<history::Session> {
+        self.ring_pending_alarm();
+        let (current_timer, next_state) = match self.state {
+            PomodoroState::Work => (&mut self.work_timer, PomodoroState::Break),
+            PomodoroState::Break => (&mut self.break_timer, PomodoroState::Work),
+        };
 
-        // Default arguments to show the message only..
-        cmd.arg("-e").arg(format!(
-            "display notification \"{}\" with title \"{}\"",
-            message, title
-        ));
+        let remaining = current_timer.remaining();
+        if remaining != time::Duration::from_secs(0) {
+            self.check_warnings(remaining);
+            return None;
+        }
 
-        if !*no_sound {
-            cmd.arg("-e").arg(format!(
-                "say \"{}\" using \"Thomas (French (France))\"",
+        let now = Local::now();
+        let session = history::Session {
+            start: self.phase_start.unwrap_or(now),
+            phase: self.state,
+            duration: current_timer.duration.as_secs(),
+            task: self.task.clone(),
+            tags: self.tags.clone(),
+            notes: String::new(),
+        };
+        current_timer.reset();
+        let (mut message, mut sound_event) = match next_state {
+            PomodoroState::Work => (self.messages.work_start.clone(), SoundEvent::WorkStart),
+            PomodoroState::Break => (self.messages.break_start.clone(), SoundEvent::BreakStart),
+        };
+        let mut goal_reached = false;
+        self.in_long_break = false;
+        if session.phase == PomodoroState::Work {
+            self.completed_sessions += 1;
+            if let Some(long_break) = self.long_break {
+                if self
+                    .completed_sessions
+                    .is_multiple_of(self.long_break_interval)
+                {
+                    self.break_timer.reset_to(long_break);
+                    self.in_long_break = true;
+                    message = self.messages.long_break_start.clone();
+                    sound_event = SoundEvent::LongBreakStart;
+                }
+            }
+            if self.goal == Some(self.completed_sessions) {
+                goal_reached = true;
+                sound_event = SoundEvent::GoalReached;
+            }
+        }
+        self.hooks.run(HookEvent::Complete, &self.hook_context());
+        self.state = next_state;
+        self.warnings_sent = 0;
+        if self.auto_start {
+            match self.state {
+                PomodoroState::Work => self.work_timer.start_or_pause(),
+                PomodoroState::Break => self.break_timer.start_or_pause(),
+            }
+            self.phase_start = Some(now);
+            self.hooks.run(self.start_event(), &self.hook_context());
+        } else {
+            self.phase_start = None;
+        }
+        self.update_ambient();
+        let placeholders = self.placeholders(self.state, time::Duration::ZERO);
+        let mut message = self.messages.render(&message, &placeholders);
+        if goal_reached {
+            message = format!(
+                "{} {}",
+                self.messages
+                    .render(&self.messages.goal_reached, &placeholders),
                 message
-            ));
+            );
+        }
+        let notification = Notification {
+            title: self.messages.render(&self.messages.title, &placeholders),
+            message,
+            phase: self.state,
+            remaining: 0,
+            work_duration: self.work_timer.duration.as_secs(),
+            break_duration: self.break_timer.duration.as_secs(),
+            completed_sessions: self.completed_sessions,
+            task: self.task.clone(),
+        };
+        self.notifier.notify(&notification);
+        let volume = match self.alarm.insistent {
+            true => self.alarm.volume(self.sounds.volume, 0),
+            false => self.sounds.volume,
+        };
+        self.play_sound(sound_event, sounds::amplitude(volume));
+        if self.alarm.insistent {
+            self.pending_alarm = Some(PendingAlarm {
+                event: sound_event,
+                notification,
+                last_ring: time::Instant::now(),
+                rings: 0,
+            });
         }
+        Some(session)
+    }
 
-        match cmd.output() {
-            Ok(_) => {}
-            Err(err) => {
-                eprintln!("Failed to send notification: {}", err);
+    /// Sends the warning of the shortest threshold the time left has gone
+    /// under, if it wasn't sent yet in this phase.
+    fn check_warnings(&mut self, remaining: time::Duration) {
+        let duration = match self.state {
+            PomodoroState::Work => self.work_timer.duration,
+            PomodoroState::Break => self.break_timer.duration,
+        };
+        let mut warning = None;
+        while let Some(&threshold) = self.warnings.get(self.warnings_sent) {
+            if remaining > threshold {
+                break;
+            }
+            if threshold < duration {
+                warning = Some(threshold);
             }
+            self.warnings_sent += 1;
         }
+        let Some(warning) = warning else {
+            return;
+        };
+        let next_phase = match self.state {
+            PomodoroState::Work => PomodoroState::Break,
+            PomodoroState::Break => PomodoroState::Work,
+        };
+        let placeholders = self.placeholders(next_phase, warning);
+        self.notifier.notify(&Notification {
+            title: self.messages.render(&self.messages.title, &placeholders),
+            message: self.messages.render(&self.messages.warning, &placeholders),
+            phase: self.state,
+            remaining: warning.as_secs(),
+            work_duration: self.work_timer.duration.as_secs(),
+            break_duration: self.break_timer.duration.as_secs(),
+            completed_sessions: self.completed_sessions,
+            task: self.task.clone(),
+        });
+        self.play_sound(SoundEvent::Warning, self.sounds.amplitude());
     }
 
-    if cfg!(target_os = "linux") {
-        let _ = Notification::new().summary(title).body(message).show();
-        let sound_clone = sound.to_path_buf();
-        if !*no_sound {
-            thread::spawn(move || {
-                sound_play(&sound_clone);
-            });
+    fn placeholders(
+        &self,
+        next_phase: PomodoroState,
+        remaining: time::Duration,
+    ) -> Placeholders<'_> {
+        Placeholders {
+            phase: self.state,
+            count: self.completed_sessions,
+            task: self.task.as_deref(),
+            next_duration: match next_phase {
+                PomodoroState::Work => self.work_timer.duration,
+                PomodoroState::Break => self.break_timer.duration,
+            },
+            remaining,
         }
     }
+
+    /// Plays the sound of the alarm waiting for an acknowledgment again, if
+    /// it is time to. Without sound, the notification is sent again instead.
+    fn ring_pending_alarm(&mut self) {
+        let can_play_sound = self.can_play_sound();
+        let Some(alarm) = &mut self.pending_alarm else {
+            return;
+        };
+        if alarm.last_ring.elapsed() < time::Duration::from_secs(self.alarm.interval) {
+            return;
+        }
+        alarm.last_ring = time::Instant::now();
+        alarm.rings += 1;
+        if !can_play_sound {
+            self.notifier.notify(&alarm.notification);
+            return;
+        }
+        let (event, rings) = (alarm.event, alarm.rings);
+        self.play_sound(
+            event,
+            sounds::amplitude(self.alarm.volume(self.sounds.volume, rings)),
+        );
+    }
+
+    /// Whether sounds are played. On MacOS, the desktop notification reads
+    /// the message out loud instead.
+    fn can_play_sound(&self) -> bool {
+        cfg!(target_os = "linux") && !self.no_sound
+    }
+
+    fn play_sound(&self, event: SoundEvent, volume: f32) {
+        if self.can_play_sound() {
+            let sound = match self.sounds.path(event) {
+                Some(path) => Sound::File(path.to_path_buf()),
+                None => self.sound.clone(),
+            };
+            let max_duration = self.sounds.max_duration.map(time::Duration::from_secs);
+            self.player.play(sound, volume, max_duration);
+        }
+    }
+}
+
+/// Plays the sound until its end, or for at most `max_duration`. The volume
+/// is a factor applied to the samples, `1.0` keeping them as they are.
+pub fn sound_play(sound: &Sound, volume: f32, max_duration: Option<time::Duration>) {
+    let (_stream, stream_handler) = match OutputStream::try_default() {
+        Ok(ok) => ok,
+        Err(_e) => return,
+    };
+    let Ok(sink) = Sink::try_new(&stream_handler) else {
+        return;
+    };
+    let decoder = match sound {
+        Sound::File(path) => fs::File::open(path)
+            .ok()
+            .and_then(|file| Decoder::new(BufReader::new(file)).ok())
+            .map(|decoder| Box::new(decoder) as Box<dyn Source<Item = i16> + Send>),
+        Sound::Embedded(bytes) => Decoder::new(Cursor::new(*bytes))
+            .ok()
+            .map(|decoder| Box::new(decoder) as Box<dyn Source<Item = i16> + Send>),
+    };
+    if let Some(decoder) = decoder {
+        let source = decoder.amplify(volume);
+        match max_duration {
+            Some(max_duration) => sink.append(source.take_duration(max_duration)),
+            None => sink.append(source),
+        }
+        sink.sleep_until_end();
+    }
+}
+
+fn get_min_sec_from_duration(duration: time::Duration) -> (u64, u64) {
+    let total_seconds = duration.as_secs();
+    let minutes = total_seconds / 60;
+    let seconds = total_seconds % 60;
+    (minutes, seconds)
 }
 
 #[cfg(test)]
@@ -222,6 +749,7 @@ mod tests {
     // Some tests for the Timer struct are included to check more
     // thoroughly the timer functionalities.
     use super::*;
+    use std::path::PathBuf;
 
     // For tests units only
     fn default_sound_path() -> PathBuf {
@@ -301,11 +829,40 @@ mod tests {
         assert_eq!(timer.to_string(), "03:05");
     }
 
+    #[test]
+    fn test_timer_progress() {
+        // Given
+        let mut timer = Timer::new(0, 2);
+        assert_eq!(timer.progress(), 0.0);
+        // When
+        timer.start_or_pause();
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        // Then
+        let progress = timer.progress();
+        assert!((0.5..1.0).contains(&progress));
+        // When
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        // Then
+        assert_eq!(timer.progress(), 1.0);
+    }
+
+    #[test]
+    fn test_timer_progress_zero_duration() {
+        let timer = Timer::new(0, 0);
+        assert_eq!(timer.progress(), 1.0);
+    }
+
     #[test]
     fn test_pomodoro_initialization() {
         // When
-        let sound = default_sound_path();
-        let pomodoro = Pomodoro::new((25, 0), (2, 5), sound, true);
+        let sound = Sound::default();
+        let pomodoro = Pomodoro::new(
+            (25, 0),
+            (2, 5),
+            sound,
+            true,
+            Box::new(notifier::NoopNotifier),
+        );
         // Then
         assert_eq!(pomodoro.work_time(), "25:00");
         assert_eq!(pomodoro.break_time(), "02:05");
@@ -316,8 +873,14 @@ mod tests {
     #[test]
     fn test_pomodoro_start_or_pause() {
         // Given
-        let sound = default_sound_path();
-        let mut pomodoro = Pomodoro::new((0, 1), (0, 5), sound, true);
+        let sound = Sound::default();
+        let mut pomodoro = Pomodoro::new(
+            (0, 1),
+            (0, 5),
+            sound,
+            true,
+            Box::new(notifier::NoopNotifier),
+        );
         // When
         pomodoro.start_or_pause();
         // Then
@@ -336,8 +899,14 @@ mod tests {
     #[test]
     fn test_pomodoro_reset() {
         // Given
-        let sound = default_sound_path();
-        let mut pomodoro = Pomodoro::new((0, 3), (0, 2), sound, true);
+        let sound = Sound::default();
+        let mut pomodoro = Pomodoro::new(
+            (0, 3),
+            (0, 2),
+            sound,
+            true,
+            Box::new(notifier::NoopNotifier),
+        );
         pomodoro.start_or_pause();
         std::thread::sleep(std::time::Duration::from_secs(1));
         // When
@@ -353,8 +922,14 @@ mod tests {
     fn test_pomodoro_reset_from_break() {
         // Given
 
-        let sound = default_sound_path();
-        let mut pomodoro = Pomodoro::new((0, 3), (0, 2), sound, true);
+        let sound = Sound::default();
+        let mut pomodoro = Pomodoro::new(
+            (0, 3),
+            (0, 2),
+            sound,
+            true,
+            Box::new(notifier::NoopNotifier),
+        );
         std::thread::sleep(std::time::Duration::from_secs(2));
         pomodoro.check_and_switch();
         // When
@@ -370,8 +945,14 @@ mod tests {
     fn test_pomodoro_check_and_switch() {
         // Given
 
-        let sound = default_sound_path();
-        let mut pomodoro = Pomodoro::new((0, 2), (0, 2), sound, true);
+        let sound = Sound::default();
+        let mut pomodoro = Pomodoro::new(
+            (0, 2),
+            (0, 2),
+            sound,
+            true,
+            Box::new(notifier::NoopNotifier),
+        );
         pomodoro.start_or_pause();
         // When
         pomodoro.check_and_switch();
@@ -379,9 +960,12 @@ mod tests {
         assert_eq!(*pomodoro.state(), PomodoroState::Work);
         // When expected to switch to break
         std::thread::sleep(std::time::Duration::from_secs(2));
-        pomodoro.check_and_switch();
+        let session = pomodoro.check_and_switch();
         // Then
         assert_eq!(*pomodoro.state(), PomodoroState::Break);
+        let session = session.expect("a work session should have been completed");
+        assert_eq!(session.phase, PomodoroState::Work);
+        assert_eq!(session.duration, 2);
         // When expected to switch to work
         std::thread::sleep(std::time::Duration::from_secs(2));
         pomodoro.check_and_switch();
@@ -389,6 +973,480 @@ mod tests {
         assert_eq!(*pomodoro.state(), PomodoroState::Work);
     }
 
+    #[test]
+    fn test_pomodoro_warnings() {
+        // Given
+        let notifier = notifier::tests::RecordingNotifier::default();
+        let mut pomodoro = Pomodoro::new(
+            (0, 3),
+            (0, 3),
+            Sound::default(),
+            true,
+            Box::new(notifier.clone()),
+        );
+        pomodoro.set_warnings(vec![
+            time::Duration::from_secs(1),
+            time::Duration::from_secs(10),
+            time::Duration::from_secs(2),
+        ]);
+        pomodoro.start_or_pause();
+        let messages = || -> Vec<(String, u64)> {
+            notifier
+                .notifications
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|notification| (notification.message.clone(), notification.remaining))
+                .collect()
+        };
+        // When the phase starts, a warning longer than the phase is not sent
+        pomodoro.check_and_switch();
+        assert!(messages().is_empty());
+        // When the time left gets under a threshold, its warning is sent
+        // once
+        std::thread::sleep(std::time::Duration::from_millis(1200));
+        pomodoro.check_and_switch();
+        pomodoro.check_and_switch();
+        assert_eq!(messages(), vec![("2 seconds left of work.".to_string(), 2)]);
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        pomodoro.check_and_switch();
+        assert_eq!(messages().len(), 2);
+        assert_eq!(messages()[1], ("1 second left of work.".to_string(), 1));
+        // When the next phase starts, warnings are sent again
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        pomodoro.check_and_switch();
+        std::thread::sleep(std::time::Duration::from_millis(1200));
+        pomodoro.check_and_switch();
+        assert_eq!(messages()[3], ("2 seconds left of break.".to_string(), 2));
+    }
+
+    #[test]
+    fn test_pomodoro_long_break_and_goal() {
+        // Given
+        let notifier = notifier::tests::RecordingNotifier::default();
+        let mut pomodoro = Pomodoro::new(
+            (0, 1),
+            (0, 1),
+            Sound::default(),
+            true,
+            Box::new(notifier.clone()),
+        );
+        pomodoro.set_long_break(time::Duration::from_secs(2), 2);
+        pomodoro.set_goal(Some(2));
+        assert_eq!(pomodoro.cycle_position(), (1, 2));
+        pomodoro.start_or_pause();
+        // When two work sessions are completed
+        for _ in 0..3 {
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            pomodoro.check_and_switch();
+        }
+        // Then the second break is a long one
+        assert_eq!(*pomodoro.state(), PomodoroState::Break);
+        assert_eq!(pomodoro.cycle_position(), (2, 2));
+        let notifications = notifier.notifications.lock().unwrap().clone();
+        assert_eq!(notifications[2].break_duration, 2);
+        let messages: Vec<String> = notifications
+            .iter()
+            .map(|notification| notification.message.clone())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "It's time to have a break.",
+                "It's time to research.",
+                "Goal of 2 sessions reached! It's time to have a long break.",
+            ]
+        );
+        // When the long break is over, the next break is a short one again
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        let session = pomodoro.check_and_switch().unwrap();
+        assert_eq!(session.duration, 2);
+        assert_eq!(pomodoro.break_time(), "00:01");
+    }
+
+    #[test]
+    fn test_pomodoro_apply_profile() {
+        // Given
+        let mut pomodoro = Pomodoro::new(
+            (25, 0),
+            (5, 0),
+            Sound::default(),
+            true,
+            Box::new(notifier::NoopNotifier),
+        );
+        pomodoro.set_goal(Some(8));
+        // When
+        pomodoro.apply_profile(&Profile {
+            work: Some(50),
+            break_time: Some(10),
+            ..Default::default()
+        });
+        // Then the durations are changed, and the goal is kept
+        assert_eq!(pomodoro.work_time(), "50:00");
+        assert_eq!(pomodoro.break_time(), "10:00");
+        assert_eq!(pomodoro.goal, Some(8));
+        // And a reset keeps the new durations
+        pomodoro.reset();
+        assert_eq!(pomodoro.work_time(), "50:00");
+    }
+
+    #[test]
+    fn test_pomodoro_ambient() {
+        // Given
+        let mut pomodoro = Pomodoro::new(
+            (0, 1),
+            (0, 1),
+            Sound::default(),
+            true,
+            Box::new(notifier::NoopNotifier),
+        );
+        // Without audio output, to not tick while the tests run
+        let (commands, played) = std::sync::mpsc::channel();
+        pomodoro.set_ambient(AmbientPlayer::with_commands(commands));
+        assert!(!pomodoro.is_ambient_playing());
+        // When the work timer runs, the track is played
+        pomodoro.start_or_pause();
+        assert!(pomodoro.is_ambient_playing());
+        // When it is toggled off and on again
+        pomodoro.toggle_ambient();
+        assert!(!pomodoro.is_ambient_playing());
+        pomodoro.toggle_ambient();
+        assert!(pomodoro.is_ambient_playing());
+        // When the timer is paused
+        pomodoro.start_or_pause();
+        assert!(!pomodoro.is_ambient_playing());
+        // When the break starts
+        pomodoro.start_or_pause();
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        pomodoro.check_and_switch();
+        assert!(!pomodoro.is_ambient_playing());
+        // When the work starts again
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        pomodoro.check_and_switch();
+        assert!(pomodoro.is_ambient_playing());
+        // The track was played and paused with the timer
+        use ambient::Command::{Pause, Play};
+        assert_eq!(
+            played.try_iter().collect::<Vec<_>>(),
+            vec![Play, Pause, Play, Pause, Play, Pause, Play]
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_pomodoro_insistent_alarm() {
+        // Given an alarm played without audio output
+        let player = sounds::tests::RecordingPlayer::default();
+        let mut pomodoro = Pomodoro::new(
+            (0, 1),
+            (0, 5),
+            Sound::default(),
+            false,
+            Box::new(notifier::NoopNotifier),
+        );
+        pomodoro.set_player(Box::new(player.clone()));
+        pomodoro.set_sounds(Sounds {
+            volume: 50,
+            ..Default::default()
+        });
+        pomodoro.set_alarm(AlarmSettings {
+            insistent: true,
+            interval: 0,
+            escalate: true,
+        });
+        pomodoro.start_or_pause();
+        // When the phase is over
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        pomodoro.check_and_switch();
+        // Then the alarm rings until acknowledged
+        assert!(pomodoro.is_awaiting_ack());
+        pomodoro.check_and_switch();
+        pomodoro.check_and_switch();
+        assert_eq!(pomodoro.pending_alarm.as_ref().unwrap().rings, 2);
+        assert!(pomodoro.is_awaiting_ack());
+        // Louder each time
+        assert_eq!(*player.volumes.lock().unwrap(), vec![0.25, 0.3, 0.35]);
+        // When acknowledged
+        pomodoro.acknowledge();
+        pomodoro.check_and_switch();
+        // Then
+        assert!(!pomodoro.is_awaiting_ack());
+        assert_eq!(*pomodoro.state(), PomodoroState::Break);
+        assert_eq!(player.volumes.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_pomodoro_set_durations_during_long_break() {
+        // Given a long break
+        let mut pomodoro = Pomodoro::new(
+            (0, 1),
+            (5, 0),
+            Sound::default(),
+            true,
+            Box::new(notifier::NoopNotifier),
+        );
+        pomodoro.set_long_break(time::Duration::from_secs(15 * 60), 1);
+        pomodoro.set_auto_start(false);
+        pomodoro.start_or_pause();
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        pomodoro.check_and_switch();
+        // When the duration of the short breaks is changed
+        pomodoro.set_break_duration(time::Duration::from_secs(10 * 60));
+        // Then the long break goes on
+        assert_eq!(pomodoro.break_time(), "15:00");
+        // When the duration of the long breaks is changed
+        pomodoro.set_long_break_duration(time::Duration::from_secs(20 * 60));
+        // Then
+        assert_eq!(pomodoro.break_time(), "20:00");
+        // When the long break is over
+        pomodoro.reset();
+        // Then the next break is a short one
+        assert_eq!(pomodoro.break_time(), "10:00");
+        // When a break as long as the long ones is changed
+        pomodoro.snooze(time::Duration::from_secs(20 * 60));
+        pomodoro.set_break_duration(time::Duration::from_secs(12 * 60));
+        // Then it isn't taken for a long break
+        assert_eq!(
+            pomodoro.break_timer.duration,
+            time::Duration::from_secs(12 * 60)
+        );
+    }
+
+    #[test]
+    fn test_pomodoro_next_duration() {
+        // Given
+        let notifier = notifier::tests::RecordingNotifier::default();
+        let mut pomodoro = Pomodoro::new(
+            (0, 1),
+            (0, 5),
+            Sound::default(),
+            true,
+            Box::new(notifier.clone()),
+        );
+        pomodoro.set_messages(Messages {
+            break_start: "Back to work in {next_duration}.".to_string(),
+            ..Default::default()
+        });
+        pomodoro.start_or_pause();
+        // When the work session is over
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        pomodoro.check_and_switch();
+        // Then the duration is the one of the break starting
+        let notifications = notifier.notifications.lock().unwrap().clone();
+        assert_eq!(notifications[0].message, "Back to work in 5 seconds.");
+    }
+
+    #[test]
+    fn test_pomodoro_insistent_alarm_without_sound() {
+        // Given
+        let notifier = notifier::tests::RecordingNotifier::default();
+        let mut pomodoro = Pomodoro::new(
+            (0, 1),
+            (0, 5),
+            Sound::default(),
+            true,
+            Box::new(notifier.clone()),
+        );
+        pomodoro.set_alarm(AlarmSettings {
+            insistent: true,
+            interval: 0,
+            ..Default::default()
+        });
+        pomodoro.start_or_pause();
+        // When the phase is over
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        pomodoro.check_and_switch();
+        // Then the notification is sent again until acknowledged
+        assert!(pomodoro.is_awaiting_ack());
+        pomodoro.check_and_switch();
+        pomodoro.check_and_switch();
+        pomodoro.acknowledge();
+        pomodoro.check_and_switch();
+        let notifications = notifier.notifications.lock().unwrap();
+        assert_eq!(notifications.len(), 3);
+        assert!(notifications
+            .iter()
+            .all(|notification| *notification == notifications[0]));
+    }
+
+    #[test]
+    fn test_pomodoro_session_task_and_tags() {
+        // Given
+        let sound = Sound::default();
+        let mut pomodoro = Pomodoro::new(
+            (0, 1),
+            (0, 1),
+            sound,
+            true,
+            Box::new(notifier::NoopNotifier),
+        );
+        pomodoro.set_task(Some("write docs".to_string()));
+        pomodoro.set_tags(vec!["docs".to_string()]);
+        pomodoro.start_or_pause();
+        // When
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        let session = pomodoro.check_and_switch().unwrap();
+        // Then
+        assert_eq!(session.task.as_deref(), Some("write docs"));
+        assert_eq!(session.tags, vec!["docs".to_string()]);
+        assert!(pomodoro.check_and_switch().is_none());
+    }
+
+    #[test]
+    fn test_pomodoro_check_and_switch_notifies() {
+        // Given
+        let sound = Sound::default();
+        let notifier = notifier::tests::RecordingNotifier::default();
+        let mut pomodoro = Pomodoro::new((0, 1), (0, 1), sound, true, Box::new(notifier.clone()));
+        pomodoro.set_task(Some("write tests".to_string()));
+        pomodoro.start_or_pause();
+        // When
+        pomodoro.check_and_switch();
+        // Then
+        assert!(notifier.notifications.lock().unwrap().is_empty());
+        // When
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        pomodoro.check_and_switch();
+        // Then
+        assert_eq!(
+            *notifier.notifications.lock().unwrap(),
+            vec![Notification {
+                title: "Pomodoro Timer".to_string(),
+                message: "It's time to have a break.".to_string(),
+                phase: PomodoroState::Break,
+                remaining: 0,
+                work_duration: 1,
+                break_duration: 1,
+                completed_sessions: 1,
+                task: Some("write tests".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_pomodoro_without_auto_start() {
+        // Given
+        let sound = Sound::default();
+        let mut pomodoro = Pomodoro::new(
+            (0, 1),
+            (0, 2),
+            sound,
+            true,
+            Box::new(notifier::NoopNotifier),
+        );
+        pomodoro.set_auto_start(false);
+        pomodoro.start_or_pause();
+        // When
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        pomodoro.check_and_switch();
+        // Then
+        assert_eq!(*pomodoro.state(), PomodoroState::Break);
+        assert!(!pomodoro.is_running());
+        assert_eq!(pomodoro.break_time(), "00:02");
+    }
+
+    #[test]
+    fn test_pomodoro_skip() {
+        // Given
+        let sound = Sound::default();
+        let mut pomodoro = Pomodoro::new(
+            (0, 3),
+            (0, 2),
+            sound,
+            true,
+            Box::new(notifier::NoopNotifier),
+        );
+        pomodoro.start_or_pause();
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        // When
+        pomodoro.skip();
+        // Then
+        assert_eq!(*pomodoro.state(), PomodoroState::Break);
+        assert!(pomodoro.is_running());
+        assert_eq!(pomodoro.work_time(), "00:03");
+        assert!(pomodoro.check_and_switch().is_none());
+    }
+
+    #[test]
+    fn test_pomodoro_snooze() {
+        // Given
+        let sound = Sound::default();
+        let mut pomodoro = Pomodoro::new(
+            (0, 1),
+            (0, 5),
+            sound,
+            true,
+            Box::new(notifier::NoopNotifier),
+        );
+        pomodoro.start_or_pause();
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        pomodoro.check_and_switch();
+        // When
+        pomodoro.snooze(time::Duration::from_secs(2));
+        // Then
+        assert_eq!(*pomodoro.state(), PomodoroState::Work);
+        assert!(pomodoro.is_running());
+        assert_eq!(pomodoro.break_time(), "00:05");
+        // When the extra time is over
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        let session = pomodoro.check_and_switch().unwrap();
+        // Then the extra time is recorded, and the work timer is back to
+        // its duration
+        assert_eq!(session.duration, 2);
+        assert_eq!(pomodoro.work_time(), "00:01");
+    }
+
+    #[test]
+    fn test_pomodoro_hooks() {
+        // Given
+        let output = hooks::tests::temp_path("pomodoro");
+        let mut pomodoro = Pomodoro::new(
+            (0, 1),
+            (0, 2),
+            Sound::default(),
+            true,
+            Box::new(notifier::NoopNotifier),
+        );
+        let log = |event: &str| {
+            Some(format!(
+                "echo \"{} $POMO_PHASE $POMO_DURATION $POMO_SESSION_COUNT\" >> {}",
+                event,
+                output.display()
+            ))
+        };
+        pomodoro.set_hooks(Hooks {
+            on_work_start: log("work_start"),
+            on_break_start: log("break_start"),
+            on_complete: log("complete"),
+            ..Default::default()
+        });
+        // When the timer is started, paused and resumed
+        pomodoro.start_or_pause();
+        pomodoro.start_or_pause();
+        pomodoro.start_or_pause();
+        let _ = hooks::tests::read_eventually(&output);
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        pomodoro.check_and_switch();
+        // Then
+        assert_eq!(pomodoro.completed_sessions(), 1);
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        let events: Vec<String> = fs::read_to_string(&output)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        // The commands run in the order of the events, the start ones when
+        // a phase begins only
+        assert_eq!(
+            events,
+            vec![
+                "work_start work 1 0",
+                "complete work 1 1",
+                "break_start break 2 1"
+            ]
+        );
+    }
+
     #[test]
     fn test_get_min_sec_from_duration() {
         let duration = time::Duration::from_secs(125);
@@ -1,8 +1,18 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+#[cfg(unix)]
+use pomodoro_tui::ipc;
+use pomodoro_tui::{
+    ambient, art, config, export, import, keys, messages, notifier, sounds, speech, theme,
+};
+use std::fs;
 use std::io;
 use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time;
 mod app;
-mod ascii_images;
+mod history_tab;
+mod palette;
+mod stats_tab;
 
 #[derive(Parser)]
 #[clap(about = "A simple Pomodoro timer")]
@@ -14,6 +24,48 @@ struct Args {
     break_time: u64,
     #[arg(short = 'i', long = "hide-image", default_value = "false")]
     hide_image: bool,
+    #[arg(
+        short = 'g',
+        long = "gauge-only",
+        help = "Replace the big timers by progress gauges, for very small panes",
+        default_value = "false"
+    )]
+    gauge_only: bool,
+    #[arg(
+        long = "compact",
+        help = "Show the timer on a single line, like the window does when it is too small for the big timers",
+        default_value = "false"
+    )]
+    compact: bool,
+    #[arg(
+        long = "inline",
+        value_name = "LINES",
+        value_parser = clap::value_parser!(u16).range(1..),
+        help = "Draw the timer on this many lines under the shell prompt, instead of taking the whole screen"
+    )]
+    inline: Option<u16>,
+    #[arg(
+        long = "no-mouse",
+        help = "Leave the mouse to the terminal, for selecting text, instead of clicking the timers and hints",
+        default_value = "false"
+    )]
+    no_mouse: bool,
+    #[arg(
+        long = "theme",
+        help = "Colors of the interface: dark, light, solarized, gruvbox, high-contrast, or a theme of the configuration file [default: dark]"
+    )]
+    theme: Option<String>,
+    #[arg(
+        long = "art",
+        help = "Art pack drawn next to the timers: a directory with work.txt and break.txt, or the name of one in the art directory of the configuration"
+    )]
+    art: Option<String>,
+    #[arg(
+        long = "no-animation",
+        help = "Show only the first frame of animated art, for slow connections like SSH",
+        default_value = "false"
+    )]
+    no_animation: bool,
     #[arg(short = 's', long = "sound")]
     sound: Option<String>,
     #[arg(
@@ -23,30 +75,567 @@ struct Args {
         default_value = "false"
     )]
     no_sound: bool,
+    #[arg(
+        long = "volume",
+        value_parser = clap::value_parser!(u8).range(0..=100),
+        help = "Volume of the sounds, from 0 to 100 [default: 100]"
+    )]
+    volume: Option<u8>,
+    #[arg(
+        long = "ambient",
+        help = "Track looped during work sessions: ticking, brown-noise or the path of an audio file"
+    )]
+    ambient: Option<String>,
+    #[arg(
+        long = "ambient-volume",
+        value_parser = clap::value_parser!(u8).range(0..=100),
+        help = "Volume of the ambient track, from 0 to 100 [default: 30]"
+    )]
+    ambient_volume: Option<u8>,
+    #[arg(
+        long = "insistent",
+        help = "Ring the alarm again until acknowledged with a key or `pomodoro-tui ack`"
+    )]
+    insistent: bool,
+    #[arg(long = "escalate", help = "Ring an insistent alarm louder every time")]
+    escalate: bool,
+    #[arg(long = "long-break", help = "Minutes of the long breaks")]
+    long_break: Option<u64>,
+    #[arg(
+        long = "long-break-every",
+        help = "Number of work sessions between two long breaks [default: 4]"
+    )]
+    long_break_interval: Option<u32>,
+    #[arg(
+        long = "goal",
+        help = "Number of work sessions to complete, a sound is played when reached"
+    )]
+    goal: Option<u32>,
+    #[arg(
+        long = "profile",
+        help = "Profile of the configuration file to apply, like [profiles.deep-work]"
+    )]
+    profile: Option<String>,
+    #[arg(
+        long = "no-auto-start",
+        help = "Wait for the next phase to be started, instead of starting it when a phase is over",
+        default_value = "false"
+    )]
+    no_auto_start: bool,
+    #[arg(
+        long = "warn",
+        help = "Minutes left in a phase when a warning is sent, can be repeated"
+    )]
+    warnings: Vec<u64>,
+    #[arg(
+        long = "notifier",
+        value_enum,
+        help = "How to notify the end of a phase, can be repeated [default: desktop]"
+    )]
+    notifiers: Vec<NotifierKind>,
+    #[arg(
+        long = "notify-command",
+        help = "Shell command run at the end of a phase, with POMO_TITLE, POMO_MESSAGE and POMO_PHASE set"
+    )]
+    notify_command: Option<String>,
+    #[arg(
+        long = "webhook",
+        help = "URL receiving a JSON POST request at the end of a phase, can be repeated"
+    )]
+    webhooks: Vec<String>,
+    #[arg(short = 't', long = "task", help = "Task recorded with the sessions")]
+    task: Option<String>,
+    #[arg(long = "tag", help = "Tag recorded with the sessions, can be repeated")]
+    tags: Vec<String>,
+    #[arg(
+        long = "config",
+        global = true,
+        help = "Path of the configuration file, defaults to the user configuration directory"
+    )]
+    config: Option<String>,
+    #[arg(
+        long = "history",
+        global = true,
+        help = "Path of the history file, defaults to the user data directory"
+    )]
+    history: Option<String>,
+    #[cfg(unix)]
+    #[arg(
+        long = "socket",
+        global = true,
+        help = "Path of the socket receiving commands like ack, defaults to the user runtime directory"
+    )]
+    socket: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Stop the alarm of the running timer, when it rings until acknowledged
+    #[cfg(unix)]
+    Ack,
+    /// Export the sessions of the history
+    Export {
+        #[arg(short, long, value_enum, default_value = "csv")]
+        format: ExportFormat,
+        #[arg(
+            long,
+            value_parser = parse_date,
+            help = "Only export sessions started on or after this date (YYYY-MM-DD)"
+        )]
+        since: Option<chrono::NaiveDate>,
+        #[arg(
+            long,
+            value_parser = parse_date,
+            help = "Only export sessions started on or before this date (YYYY-MM-DD)"
+        )]
+        until: Option<chrono::NaiveDate>,
+        #[arg(
+            short,
+            long,
+            help = "File to write to, defaults to the standard output"
+        )]
+        output: Option<String>,
+    },
+    /// Import sessions from another Pomodoro tool into the history
+    Import {
+        /// File to import
+        file: String,
+        #[arg(short, long, value_enum, default_value = "csv")]
+        format: ImportFormat,
+        #[arg(
+            short,
+            long,
+            help = "CSV columns of the session fields, as field=column pairs separated by commas \
+                    (fields: start, end, phase, duration, task, tags, notes)"
+        )]
+        map: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "seconds",
+            help = "Unit of the CSV duration column"
+        )]
+        duration_unit: DurationUnit,
+        #[arg(
+            long,
+            help = "Report what would be imported without changing the history"
+        )]
+        dry_run: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ImportFormat {
+    Csv,
+    Timewarrior,
+    Json,
+}
+
+impl From<ImportFormat> for import::Format {
+    fn from(format: ImportFormat) -> Self {
+        match format {
+            ImportFormat::Csv => import::Format::Csv,
+            ImportFormat::Timewarrior => import::Format::Timewarrior,
+            ImportFormat::Json => import::Format::Json,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DurationUnit {
+    Seconds,
+    Minutes,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum NotifierKind {
+    /// Desktop notification
+    Desktop,
+    /// Terminal bell
+    Bell,
+    /// Terminal bell, OSC 9 and OSC 777 escape sequences, and tmux message
+    Terminal,
+    /// Message read out loud, with espeak-ng or spd-say on Linux and say on MacOS
+    Speech,
+    /// No notification
+    None,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Json,
+    Ics,
+}
+
+impl From<ExportFormat> for export::Format {
+    fn from(format: ExportFormat) -> Self {
+        match format {
+            ExportFormat::Csv => export::Format::Csv,
+            ExportFormat::Json => export::Format::Json,
+            ExportFormat::Ics => export::Format::Ics,
+        }
+    }
+}
+
+fn parse_date(date: &str) -> Result<chrono::NaiveDate, String> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|err| format!("expected a YYYY-MM-DD date: {}", err))
+}
+
+fn import_history(
+    history: &mut pomodoro_tui::history::History,
+    file: &str,
+    format: ImportFormat,
+    map: Option<String>,
+    duration_unit: DurationUnit,
+    dry_run: bool,
+) -> io::Result<()> {
+    let mut mapping = import::ColumnMapping::parse(map.as_deref().unwrap_or(""))
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    mapping.duration_unit = match duration_unit {
+        DurationUnit::Seconds => import::DurationUnit::Seconds,
+        DurationUnit::Minutes => import::DurationUnit::Minutes,
+    };
+    let report = import::import(
+        io::BufReader::new(fs::File::open(file)?),
+        format.into(),
+        &mapping,
+        history.sessions(),
+    )?;
+
+    for error in &report.errors {
+        eprintln!("Skipped {}", error);
+    }
+    for conflict in &report.conflicts {
+        eprintln!("Conflict: {}", conflict);
+    }
+    let imported = report.sessions.len();
+    if !dry_run {
+        history.add_all(report.sessions)?;
+    }
+    println!(
+        "{} {} session(s), skipped {} duplicate(s), {} conflict(s) and {} invalid record(s)",
+        if dry_run { "Would import" } else { "Imported" },
+        imported,
+        report.duplicates,
+        report.conflicts.len(),
+        report.errors.len(),
+    );
+    Ok(())
+}
+
+fn get_notifier(
+    args: &Args,
+    config: &config::Config,
+    terminal_output: &mpsc::Sender<Vec<u8>>,
+    actions: &mpsc::Sender<notifier::NotificationAction>,
+) -> Box<dyn notifier::Notifier> {
+    let kinds = match args.notifiers.is_empty() {
+        // The message has always been read out loud on MacOS
+        true if cfg!(target_os = "macos") && !args.no_sound => {
+            vec![NotifierKind::Desktop, NotifierKind::Speech]
+        }
+        true => vec![NotifierKind::Desktop],
+        false => args.notifiers.clone(),
+    };
+    let mut notifiers: Vec<Box<dyn notifier::Notifier>> = Vec::new();
+    for kind in kinds {
+        match kind {
+            NotifierKind::Desktop => notifiers.push(Box::new(
+                notifier::DesktopNotifier::new().with_actions(actions.clone()),
+            )),
+            NotifierKind::Bell => notifiers.push(Box::new(notifier::TerminalNotifier::bell(
+                terminal_output.clone(),
+            ))),
+            NotifierKind::Terminal => notifiers.push(Box::new(notifier::TerminalNotifier::new(
+                terminal_output.clone(),
+            ))),
+            NotifierKind::Speech => match speech::SpeechNotifier::new(&config.speech) {
+                Some(speech) => notifiers.push(Box::new(
+                    speech.with_messages(messages::Messages::from_settings(&config.messages)),
+                )),
+                None => eprintln!(
+                    "Failed to set up the speech notifier: none of espeak-ng, spd-say or say is installed"
+                ),
+            },
+            NotifierKind::None => notifiers.push(Box::new(notifier::NoopNotifier)),
+        }
+    }
+    if let Some(command) = &args.notify_command {
+        notifiers.push(Box::new(notifier::CommandNotifier::new(command)));
+    }
+    for url in args.webhooks.iter().chain(&config.webhooks.urls) {
+        notifiers.push(Box::new(notifier::WebhookNotifier::new(
+            url,
+            &config.webhooks,
+        )));
+    }
+    Box::new(notifier::CompositeNotifier::new(notifiers))
+}
+
+/// Sets the terminal up for the interface: the alternate screen, or a
+/// viewport of a few lines under the prompt with `--inline`. The terminal is
+/// restored if this fails, or if the program panics.
+fn init_terminal(inline: Option<u16>, mouse: bool) -> io::Result<ratatui::DefaultTerminal> {
+    let setup = || {
+        crossterm::terminal::enable_raw_mode()?;
+        let viewport = match inline {
+            Some(height) => ratatui::Viewport::Inline(height),
+            None => {
+                crossterm::execute!(io::stdout(), crossterm::terminal::EnterAlternateScreen)?;
+                ratatui::Viewport::Fullscreen
+            }
+        };
+        if mouse {
+            crossterm::execute!(io::stdout(), crossterm::event::EnableMouseCapture)?;
+        }
+        let backend = ratatui::backend::CrosstermBackend::new(io::stdout());
+        ratatui::Terminal::with_options(backend, ratatui::TerminalOptions { viewport })
+    };
+    let terminal = setup().inspect_err(|_| {
+        let _ = restore_terminal(inline.is_some(), mouse);
+    })?;
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // The panic message is printed once the terminal is back to normal
+        let _ = restore_terminal(inline.is_some(), mouse);
+        hook(info);
+    }));
+    Ok(terminal)
+}
+
+/// Undoes `init_terminal`. Every step is run even if one fails, and the
+/// first error is returned.
+fn restore_terminal(inline: bool, mouse: bool) -> io::Result<()> {
+    let steps = [
+        mouse.then(|| crossterm::execute!(io::stdout(), crossterm::event::DisableMouseCapture)),
+        Some(crossterm::terminal::disable_raw_mode()),
+        (!inline)
+            .then(|| crossterm::execute!(io::stdout(), crossterm::terminal::LeaveAlternateScreen)),
+    ];
+    steps.into_iter().flatten().fold(Ok(()), Result::and)
 }
 
 fn main() -> io::Result<()> {
     let args = Args::parse();
 
+    // Commands are sent to a running timer through a Unix socket
+    #[cfg(unix)]
+    let socket_path = match &args.socket {
+        Some(socket) => PathBuf::from(socket),
+        None => ipc::default_socket_path(),
+    };
+
+    #[cfg(unix)]
+    if let Some(Command::Ack) = args.command {
+        return ipc::send(&socket_path, ipc::Command::Ack).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!(
+                    "no timer is listening on {}: {}",
+                    socket_path.display(),
+                    err
+                ),
+            )
+        });
+    }
+
     let sound = match &args.sound {
-        Some(sound) => PathBuf::from(sound.to_owned()),
-        None => PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("default_sound.mp3"),
+        Some(sound) => sounds::Sound::File(PathBuf::from(sound)),
+        None => sounds::Sound::default(),
+    };
+
+    let history_path = match &args.history {
+        Some(history) => PathBuf::from(history),
+        None => pomodoro_tui::history::History::default_path(),
+    };
+    let history = pomodoro_tui::history::History::load(&history_path)?;
+    if history.skipped() > 0 && args.command.is_some() {
+        eprintln!(
+            "Warning: skipped {} invalid lines of {}",
+            history.skipped(),
+            history_path.display()
+        );
+    }
+
+    if let Some(Command::Export {
+        format,
+        since,
+        until,
+        output,
+    }) = args.command
+    {
+        let sessions = export::filter_by_date(history.sessions(), since, until);
+        let mut writer: Box<dyn io::Write> = match output {
+            Some(output) => Box::new(io::BufWriter::new(fs::File::create(output)?)),
+            None => Box::new(io::stdout().lock()),
+        };
+        export::export(&sessions, format.into(), &mut writer)?;
+        return writer.flush();
+    }
+
+    if let Some(Command::Import {
+        file,
+        format,
+        map,
+        duration_unit,
+        dry_run,
+    }) = args.command
+    {
+        let mut history = history;
+        return import_history(&mut history, &file, format, map, duration_unit, dry_run);
+    }
+
+    let config_path = match &args.config {
+        Some(config) => PathBuf::from(config),
+        None => config::Config::default_path(),
+    };
+    let config = config::Config::load(&config_path)?;
+    let keys = keys::KeyBindings::new(&config.keys).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid key bindings in {}: {}", config_path.display(), err),
+        )
+    })?;
+    // Colors are left out with NO_COLOR, unless a theme is chosen
+    let theme_name = args.theme.as_ref().or(config.theme.as_ref());
+    let no_color = theme_name.is_none() && theme::Theme::is_color_disabled();
+    let theme = match theme_name {
+        Some(name) => theme::Theme::resolve(name, &config.themes).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} in {}", err, config_path.display()),
+            )
+        })?,
+        None if no_color => theme::Theme::monochrome(),
+        None => theme::Theme::default(),
+    };
+    let mut art_settings = config.art.clone();
+    if let Some(pack) = &args.art {
+        art_settings.pack = Some(pack.clone());
+    }
+    let mut art = art::ArtPack::from_settings(&art_settings)?;
+    if no_color {
+        art = art.without_colors();
+    }
+
+    // Terminal notifications are written by the app, between two draws
+    let (terminal_tx, terminal_rx) = mpsc::channel();
+    // Actions chosen in the desktop notifications are applied by the app
+    let (actions_tx, actions_rx) = mpsc::channel();
+    // Commands sent by other processes are applied by the app too. Another
+    // timer may already be listening, in which case this one doesn't.
+    #[cfg(unix)]
+    let (commands_tx, commands_rx) = mpsc::channel();
+    #[cfg(unix)]
+    let (_listener, listener_error) = match ipc::Listener::bind(&socket_path, commands_tx) {
+        Ok(listener) => (Some(listener), None),
+        Err(err) => (
+            None,
+            Some(format!(
+                "`pomodoro-tui ack` won't reach this timer: {}",
+                err
+            )),
+        ),
     };
 
-    let terminal = ratatui::init();
+    let mut pomo = pomodoro_tui::Pomodoro::new(
+        (args.work, 0),
+        (args.break_time, 0),
+        sound,
+        args.no_sound,
+        get_notifier(&args, &config, &terminal_tx, &actions_tx),
+    );
+    pomo.set_task(args.task);
+    pomo.set_tags(args.tags);
+    pomo.set_hooks(config.hooks);
+    pomo.set_messages(messages::Messages::from_settings(&config.messages));
+    pomo.set_auto_start(!args.no_auto_start);
+    if let Some(long_break) = args.long_break.or(config.long_break) {
+        pomo.set_long_break(
+            time::Duration::from_secs(long_break * 60),
+            args.long_break_interval
+                .or(config.long_break_interval)
+                .unwrap_or(4),
+        );
+    }
+    pomo.set_goal(args.goal.or(config.goal));
+    let mut sounds = config.sounds;
+    if let Some(volume) = args.volume {
+        sounds.volume = volume;
+    }
+    pomo.set_sounds(sounds);
+    let mut alarm = config.alarm;
+    alarm.insistent |= args.insistent;
+    alarm.escalate |= args.escalate;
+    pomo.set_alarm(alarm);
+    if let Some(track) = args.ambient.as_ref().or(config.ambient.track.as_ref()) {
+        if !args.no_sound {
+            pomo.set_ambient(ambient::AmbientPlayer::new(
+                ambient::AmbientTrack::parse(track),
+                args.ambient_volume.unwrap_or(config.ambient.volume),
+            ));
+        }
+    }
+    let warnings = match args.warnings.is_empty() {
+        true => &config.warnings,
+        false => &args.warnings,
+    };
+    pomo.set_warnings(
+        warnings
+            .iter()
+            .map(|minutes| time::Duration::from_secs(minutes * 60))
+            .collect(),
+    );
+    if let Some(name) = &args.profile {
+        let profile = config.profiles.get(name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("no profile `{}` in {}", name, config_path.display()),
+            )
+        })?;
+        pomo.apply_profile(profile);
+    }
+
+    // The mouse is left to the shell around an inline timer
+    let mouse = !args.no_mouse && args.inline.is_none();
+    let mut terminal = init_terminal(args.inline, mouse)?;
 
-    // Pass the whole structure make it easier to manage
     let mut app = app::App::new(
-        args.work,
-        args.break_time,
+        pomo,
+        history,
+        terminal_rx,
+        actions_rx,
         args.hide_image,
-        &sound,
-        args.no_sound,
+        args.gauge_only,
     );
 
+    #[cfg(unix)]
+    {
+        app.set_commands(commands_rx);
+        // Printed, it would be hidden by the timer
+        if let Some(error) = listener_error {
+            app.set_status(error);
+        }
+    }
+    app.set_key_bindings(keys);
+    app.set_theme(theme);
+    app.set_art(art);
+    app.set_compact(args.compact);
+    if args.no_animation || !config.art.animate {
+        app.disable_animation();
+    }
+    app.set_profiles(config.profiles);
     app.handle_inputs();
     app.start_or_pause();
-    let result = app.run(terminal);
-    ratatui::restore();
+    let mut result = app.run(&mut terminal);
+    if args.inline.is_some() {
+        // The prompt comes back where the timer was
+        result = result.and(terminal.clear());
+    }
+    restore_terminal(args.inline.is_some(), mouse)?;
     result
 }
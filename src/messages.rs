@@ -0,0 +1,365 @@
+use std::env;
+use std::time;
+
+use serde::Deserialize;
+
+use crate::PomodoroState;
+
+/// Language of the built-in messages.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum Language {
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "fr")]
+    French,
+    #[serde(rename = "de")]
+    German,
+    #[serde(rename = "es")]
+    Spanish,
+}
+
+impl Language {
+    /// Language of a locale like `fr_FR.UTF-8`, if there are messages for it.
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        match locale.get(..2)? {
+            "en" => Some(Language::English),
+            "fr" => Some(Language::French),
+            "de" => Some(Language::German),
+            "es" => Some(Language::Spanish),
+            _ => None,
+        }
+    }
+
+    /// Language of the locale of the environment, from `LC_ALL`,
+    /// `LC_MESSAGES` or `LANG`, English by default.
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|locale| !locale.is_empty())
+            .and_then(|locale| Language::from_locale(&locale))
+            .unwrap_or(Language::English)
+    }
+}
+
+/// Messages set in the `[messages]` configuration section, replacing the
+/// built-in ones.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MessageSettings {
+    /// Language of the built-in messages, from the environment by default
+    pub language: Option<Language>,
+    pub title: Option<String>,
+    pub work_start: Option<String>,
+    pub break_start: Option<String>,
+    pub long_break_start: Option<String>,
+    pub goal_reached: Option<String>,
+    pub warning: Option<String>,
+}
+
+/// Values replacing the placeholders of the message templates.
+pub struct Placeholders<'a> {
+    /// Phase starting, or ending soon for a warning
+    pub phase: PomodoroState,
+    /// Number of work sessions completed so far
+    pub count: u32,
+    pub task: Option<&'a str>,
+    /// Duration of the phase starting, or of the phase after the one ending
+    /// soon for a warning
+    pub next_duration: time::Duration,
+    /// Time left in the phase, for a warning
+    pub remaining: time::Duration,
+}
+
+/// Texts of the notifications and of the interface, in one language.
+///
+/// The notification messages are templates, where `{phase}`, `{count}`,
+/// `{task}`, `{next_duration}` and `{remaining}` are replaced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Messages {
+    pub title: String,
+    pub work_start: String,
+    pub break_start: String,
+    pub long_break_start: String,
+    /// Put before the break start message when the goal is reached
+    pub goal_reached: String,
+    pub warning: String,
+    pub work_phase: String,
+    pub break_phase: String,
+    pub minute: String,
+    pub minutes: String,
+    pub second: String,
+    pub seconds: String,
+    pub start: String,
+    pub pause: String,
+    pub reset: String,
+    pub skip: String,
+    pub ambient: String,
+    pub next_tab: String,
+    pub quit: String,
+    pub help: String,
+    pub commands: String,
+    pub stop_alarm: String,
+    pub any_key: String,
+    pub timer_tab: String,
+    pub history_tab: String,
+    pub stats_tab: String,
+}
+
+impl Default for Messages {
+    fn default() -> Self {
+        Messages::new(Language::English)
+    }
+}
+
+impl Messages {
+    /// Built-in messages of the language.
+    pub fn new(language: Language) -> Self {
+        match language {
+            Language::English => Messages {
+                title: "Pomodoro Timer".to_string(),
+                work_start: "It's time to research.".to_string(),
+                break_start: "It's time to have a break.".to_string(),
+                long_break_start: "It's time to have a long break.".to_string(),
+                goal_reached: "Goal of {count} sessions reached!".to_string(),
+                warning: "{remaining} left of {phase}.".to_string(),
+                work_phase: "work".to_string(),
+                break_phase: "break".to_string(),
+                minute: "minute".to_string(),
+                minutes: "minutes".to_string(),
+                second: "second".to_string(),
+                seconds: "seconds".to_string(),
+                start: "Start".to_string(),
+                pause: "Pause".to_string(),
+                reset: "Reset".to_string(),
+                skip: "Skip".to_string(),
+                ambient: "Ambient".to_string(),
+                next_tab: "Next tab".to_string(),
+                quit: "Quit".to_string(),
+                help: "Help".to_string(),
+                commands: "Commands".to_string(),
+                stop_alarm: "Stop the alarm".to_string(),
+                any_key: "Any key".to_string(),
+                timer_tab: "Timer".to_string(),
+                history_tab: "History".to_string(),
+                stats_tab: "Stats".to_string(),
+            },
+            Language::French => Messages {
+                title: "Minuteur Pomodoro".to_string(),
+                work_start: "C'est l'heure de se remettre au travail.".to_string(),
+                break_start: "C'est l'heure de faire une pause.".to_string(),
+                long_break_start: "C'est l'heure de faire une longue pause.".to_string(),
+                goal_reached: "Objectif de {count} sessions atteint !".to_string(),
+                warning: "Plus que {remaining} de {phase}.".to_string(),
+                work_phase: "travail".to_string(),
+                break_phase: "pause".to_string(),
+                minute: "minute".to_string(),
+                minutes: "minutes".to_string(),
+                second: "seconde".to_string(),
+                seconds: "secondes".to_string(),
+                start: "Démarrer".to_string(),
+                pause: "Pause".to_string(),
+                reset: "Réinitialiser".to_string(),
+                skip: "Passer".to_string(),
+                ambient: "Ambiance".to_string(),
+                next_tab: "Onglet suivant".to_string(),
+                quit: "Quitter".to_string(),
+                help: "Aide".to_string(),
+                commands: "Commandes".to_string(),
+                stop_alarm: "Arrêter l'alarme".to_string(),
+                any_key: "Une touche".to_string(),
+                timer_tab: "Minuteur".to_string(),
+                history_tab: "Historique".to_string(),
+                stats_tab: "Statistiques".to_string(),
+            },
+            Language::German => Messages {
+                title: "Pomodoro-Timer".to_string(),
+                work_start: "Zeit, wieder zu arbeiten.".to_string(),
+                break_start: "Zeit für eine Pause.".to_string(),
+                long_break_start: "Zeit für eine lange Pause.".to_string(),
+                goal_reached: "Ziel von {count} Einheiten erreicht!".to_string(),
+                warning: "{phase}: noch {remaining}.".to_string(),
+                work_phase: "Arbeit".to_string(),
+                break_phase: "Pause".to_string(),
+                minute: "Minute".to_string(),
+                minutes: "Minuten".to_string(),
+                second: "Sekunde".to_string(),
+                seconds: "Sekunden".to_string(),
+                start: "Start".to_string(),
+                pause: "Pause".to_string(),
+                reset: "Zurücksetzen".to_string(),
+                skip: "Überspringen".to_string(),
+                ambient: "Hintergrund".to_string(),
+                next_tab: "Nächster Tab".to_string(),
+                quit: "Beenden".to_string(),
+                help: "Hilfe".to_string(),
+                commands: "Befehle".to_string(),
+                stop_alarm: "Alarm stoppen".to_string(),
+                any_key: "Beliebige Taste".to_string(),
+                timer_tab: "Timer".to_string(),
+                history_tab: "Verlauf".to_string(),
+                stats_tab: "Statistik".to_string(),
+            },
+            Language::Spanish => Messages {
+                title: "Temporizador Pomodoro".to_string(),
+                work_start: "Es hora de volver al trabajo.".to_string(),
+                break_start: "Es hora de tomar un descanso.".to_string(),
+                long_break_start: "Es hora de tomar un descanso largo.".to_string(),
+                goal_reached: "¡Objetivo de {count} sesiones alcanzado!".to_string(),
+                warning: "Quedan {remaining} de {phase}.".to_string(),
+                work_phase: "trabajo".to_string(),
+                break_phase: "descanso".to_string(),
+                minute: "minuto".to_string(),
+                minutes: "minutos".to_string(),
+                second: "segundo".to_string(),
+                seconds: "segundos".to_string(),
+                start: "Iniciar".to_string(),
+                pause: "Pausar".to_string(),
+                reset: "Reiniciar".to_string(),
+                skip: "Saltar".to_string(),
+                ambient: "Ambiente".to_string(),
+                next_tab: "Pestaña siguiente".to_string(),
+                quit: "Salir".to_string(),
+                help: "Ayuda".to_string(),
+                commands: "Comandos".to_string(),
+                stop_alarm: "Detener la alarma".to_string(),
+                any_key: "Cualquier tecla".to_string(),
+                timer_tab: "Temporizador".to_string(),
+                history_tab: "Historial".to_string(),
+                stats_tab: "Estadísticas".to_string(),
+            },
+        }
+    }
+
+    /// Built-in messages of the language of the settings, or of the
+    /// environment, with the messages of the settings replacing them.
+    pub fn from_settings(settings: &MessageSettings) -> Self {
+        let mut messages = Messages::new(settings.language.unwrap_or_else(Language::from_env));
+        let overrides = [
+            (&mut messages.title, &settings.title),
+            (&mut messages.work_start, &settings.work_start),
+            (&mut messages.break_start, &settings.break_start),
+            (&mut messages.long_break_start, &settings.long_break_start),
+            (&mut messages.goal_reached, &settings.goal_reached),
+            (&mut messages.warning, &settings.warning),
+        ];
+        for (message, setting) in overrides {
+            if let Some(setting) = setting {
+                *message = setting.clone();
+            }
+        }
+        messages
+    }
+
+    pub fn phase(&self, phase: PomodoroState) -> &str {
+        match phase {
+            PomodoroState::Work => &self.work_phase,
+            PomodoroState::Break => &self.break_phase,
+        }
+    }
+
+    /// Formats a duration for people, like `5 minutes` or `30 seconds`.
+    pub fn duration(&self, duration: time::Duration) -> String {
+        let seconds = duration.as_secs();
+        let (count, one, many) = if seconds >= 60 && seconds.is_multiple_of(60) {
+            (seconds / 60, &self.minute, &self.minutes)
+        } else {
+            (seconds, &self.second, &self.seconds)
+        };
+        match count {
+            1 => format!("1 {}", one),
+            _ => format!("{} {}", count, many),
+        }
+    }
+
+    /// Replaces the placeholders of the template.
+    pub fn render(&self, template: &str, placeholders: &Placeholders) -> String {
+        template
+            .replace("{phase}", self.phase(placeholders.phase))
+            .replace("{count}", &placeholders.count.to_string())
+            .replace("{task}", placeholders.task.unwrap_or(""))
+            .replace(
+                "{next_duration}",
+                &self.duration(placeholders.next_duration),
+            )
+            .replace("{remaining}", &self.duration(placeholders.remaining))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders() -> Placeholders<'static> {
+        Placeholders {
+            phase: PomodoroState::Break,
+            count: 3,
+            task: Some("write tests"),
+            next_duration: time::Duration::from_secs(25 * 60),
+            remaining: time::Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn test_language_from_locale() {
+        assert_eq!(Language::from_locale("fr_FR.UTF-8"), Some(Language::French));
+        assert_eq!(Language::from_locale("de"), Some(Language::German));
+        assert_eq!(Language::from_locale("C"), None);
+        assert_eq!(Language::from_locale("ja_JP.UTF-8"), None);
+    }
+
+    #[test]
+    fn test_messages_render() {
+        let messages = Messages::default();
+        assert_eq!(
+            messages.render(
+                "{phase} {count} for {task}, {next_duration} next, {remaining} left",
+                &placeholders()
+            ),
+            "break 3 for write tests, 25 minutes next, 1 minute left"
+        );
+        let messages = Messages::new(Language::French);
+        assert_eq!(
+            messages.render(&messages.warning, &placeholders()),
+            "Plus que 1 minute de pause."
+        );
+    }
+
+    #[test]
+    fn test_messages_duration() {
+        let messages = Messages::default();
+        assert_eq!(
+            messages.duration(time::Duration::from_secs(300)),
+            "5 minutes"
+        );
+        assert_eq!(messages.duration(time::Duration::from_secs(60)), "1 minute");
+        assert_eq!(
+            messages.duration(time::Duration::from_secs(90)),
+            "90 seconds"
+        );
+        assert_eq!(messages.duration(time::Duration::from_secs(1)), "1 second");
+        let messages = Messages::new(Language::German);
+        assert_eq!(
+            messages.duration(time::Duration::from_secs(300)),
+            "5 Minuten"
+        );
+    }
+
+    #[test]
+    fn test_messages_from_settings() {
+        // Given
+        let settings: MessageSettings = toml::from_str(
+            r#"
+            language = "es"
+            break_start = "Break! {count} sessions done."
+            "#,
+        )
+        .unwrap();
+        // When
+        let messages = Messages::from_settings(&settings);
+        // Then
+        assert_eq!(messages.break_start, "Break! {count} sessions done.");
+        assert_eq!(messages.work_start, "Es hora de volver al trabajo.");
+        assert_eq!(messages.quit, "Salir");
+    }
+}
@@ -0,0 +1,631 @@
+use std::process;
+use std::sync::mpsc;
+use std::thread;
+use std::time;
+
+use serde::{Deserialize, Serialize};
+
+use crate::PomodoroState;
+
+/// Notification sent when a phase is over, or is about to be.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notification {
+    pub title: String,
+    pub message: String,
+    /// Phase starting with this notification, or ending soon for a warning
+    pub phase: PomodoroState,
+    /// Seconds left before the end of the phase for a warning, zero when
+    /// the phase is over
+    pub remaining: u64,
+    /// Durations of the work and break phases, in seconds
+    pub work_duration: u64,
+    pub break_duration: u64,
+    /// Number of work sessions completed so far
+    pub completed_sessions: u32,
+    pub task: Option<String>,
+}
+
+/// Action chosen by the user in a notification.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationAction {
+    /// Start the phase announced by the notification
+    Start,
+    /// Skip the phase announced by the notification
+    Skip,
+    /// Give a few more minutes to the phase which just ended
+    Snooze,
+}
+
+impl NotificationAction {
+    const ALL: [NotificationAction; 3] = [
+        NotificationAction::Start,
+        NotificationAction::Skip,
+        NotificationAction::Snooze,
+    ];
+
+    fn id(&self) -> &'static str {
+        match self {
+            NotificationAction::Start => "start",
+            NotificationAction::Skip => "skip",
+            NotificationAction::Snooze => "snooze",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Self> {
+        NotificationAction::ALL
+            .into_iter()
+            .find(|action| action.id() == id)
+    }
+
+    fn label(&self, phase: PomodoroState) -> &'static str {
+        match (self, phase) {
+            (NotificationAction::Start, PomodoroState::Work) => "Start work",
+            (NotificationAction::Start, PomodoroState::Break) => "Start break",
+            (NotificationAction::Skip, _) => "Skip",
+            (NotificationAction::Snooze, _) => "+5 min",
+        }
+    }
+}
+
+/// Something notifying the user that a phase is over.
+pub trait Notifier: Send {
+    fn notify(&self, notification: &Notification);
+}
+
+/// Desktop notification, through `osascript` on MacOS and the notification
+/// server on Linux.
+#[derive(Default)]
+pub struct DesktopNotifier {
+    actions: Option<mpsc::Sender<NotificationAction>>,
+}
+
+impl DesktopNotifier {
+    pub fn new() -> Self {
+        DesktopNotifier::default()
+    }
+
+    /// Adds actions to the notifications, on Linux. The action chosen by the
+    /// user is sent to the given channel.
+    pub fn with_actions(mut self, actions: mpsc::Sender<NotificationAction>) -> Self {
+        self.actions = Some(actions);
+        self
+    }
+}
+
+impl Notifier for DesktopNotifier {
+    fn notify(&self, notification: &Notification) {
+        if cfg!(target_os = "macos") {
+            let mut cmd = process::Command::new("osascript");
+
+            // The texts are passed as arguments of the script, so that
+            // their quotes aren't read as AppleScript
+            cmd.args([
+                "-e",
+                "on run argv",
+                "-e",
+                "display notification (item 1 of argv) with title (item 2 of argv)",
+                "-e",
+                "end run",
+                &notification.message,
+                &notification.title,
+            ]);
+
+            match cmd.output() {
+                Ok(_) => {}
+                Err(err) => {
+                    eprintln!("Failed to send notification: {}", err);
+                }
+            }
+        }
+
+        if cfg!(target_os = "linux") {
+            let mut desktop_notification = notify_rust::Notification::new();
+            desktop_notification
+                .summary(&notification.title)
+                .body(&notification.message);
+            // Warnings come before the end of the phase, when there is
+            // nothing to act on yet
+            if self.actions.is_some() && notification.remaining == 0 {
+                for action in NotificationAction::ALL {
+                    desktop_notification.action(action.id(), action.label(notification.phase));
+                }
+            }
+            match (desktop_notification.show(), &self.actions) {
+                (Ok(handle), Some(actions)) if notification.remaining == 0 => {
+                    wait_for_action(handle, actions.clone())
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Sends the action chosen in the notification to the channel, once the
+/// user acts on it.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn wait_for_action(
+    handle: notify_rust::NotificationHandle,
+    actions: mpsc::Sender<NotificationAction>,
+) {
+    thread::spawn(move || {
+        handle.wait_for_action(|id| {
+            if let Some(action) = NotificationAction::from_id(id) {
+                let _ = actions.send(action);
+            }
+        })
+    });
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn wait_for_action<T>(_handle: T, _actions: mpsc::Sender<NotificationAction>) {}
+
+/// Notification inside the terminal, for sessions without a desktop like
+/// over SSH: the terminal bell, OSC 9 and OSC 777 desktop notification
+/// escape sequences, and a tmux message when running in tmux.
+///
+/// The bytes to write are sent to a channel rather than written directly, so
+/// that the owner of the terminal writes them without corrupting its UI.
+pub struct TerminalNotifier {
+    output: mpsc::Sender<Vec<u8>>,
+    escape_sequences: bool,
+    tmux: bool,
+}
+
+impl TerminalNotifier {
+    pub fn new(output: mpsc::Sender<Vec<u8>>) -> Self {
+        TerminalNotifier {
+            output,
+            escape_sequences: true,
+            tmux: std::env::var_os("TMUX").is_some(),
+        }
+    }
+
+    /// Only rings the terminal bell.
+    pub fn bell(output: mpsc::Sender<Vec<u8>>) -> Self {
+        TerminalNotifier {
+            output,
+            escape_sequences: false,
+            tmux: false,
+        }
+    }
+
+    fn escape_sequences(&self, notification: &Notification) -> Vec<u8> {
+        let mut bytes = b"\x07".to_vec();
+        if !self.escape_sequences {
+            return bytes;
+        }
+        // Control characters would end the sequences early, and semicolons
+        // separate the OSC 777 parameters
+        let title = sanitize(&notification.title).replace(';', ",");
+        let message = sanitize(&notification.message);
+        for sequence in [
+            format!("\x1b]9;{}: {}\x07", title, message),
+            format!("\x1b]777;notify;{};{}\x07", title, message),
+        ] {
+            let sequence = match self.tmux {
+                true => tmux_passthrough(&sequence),
+                false => sequence,
+            };
+            bytes.extend(sequence.into_bytes());
+        }
+        bytes
+    }
+}
+
+impl Notifier for TerminalNotifier {
+    fn notify(&self, notification: &Notification) {
+        let _ = self.output.send(self.escape_sequences(notification));
+        if self.tmux && self.escape_sequences {
+            let mut cmd = process::Command::new("tmux");
+            cmd.arg("display-message").arg(format!(
+                "{}: {}",
+                notification.title.replace('#', "##"),
+                notification.message.replace('#', "##")
+            ));
+            if let Ok(mut child) = cmd
+                .stdout(process::Stdio::null())
+                .stderr(process::Stdio::null())
+                .spawn()
+            {
+                thread::spawn(move || child.wait());
+            }
+        }
+    }
+}
+
+fn sanitize(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// Wraps an escape sequence so that tmux passes it to the outer terminal.
+fn tmux_passthrough(sequence: &str) -> String {
+    format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+}
+
+/// Runs a shell command, with the notification in the `POMO_TITLE`,
+/// `POMO_MESSAGE` and `POMO_PHASE` environment variables.
+pub struct CommandNotifier {
+    command: String,
+}
+
+impl CommandNotifier {
+    pub fn new(command: &str) -> Self {
+        CommandNotifier {
+            command: command.to_string(),
+        }
+    }
+}
+
+impl Notifier for CommandNotifier {
+    fn notify(&self, notification: &Notification) {
+        let mut cmd = process::Command::new("sh");
+        cmd.arg("-c")
+            .arg(&self.command)
+            .env("POMO_TITLE", &notification.title)
+            .env("POMO_MESSAGE", &notification.message)
+            .env("POMO_PHASE", notification.phase.name())
+            .env("POMO_REMAINING", notification.remaining.to_string())
+            .stdin(process::Stdio::null())
+            .stdout(process::Stdio::null())
+            .stderr(process::Stdio::null());
+        // Wait for the command in the background, to not leave a zombie
+        // process behind
+        if let Ok(mut child) = cmd.spawn() {
+            thread::spawn(move || child.wait());
+        }
+    }
+}
+
+/// Settings of the webhooks, from the `[webhooks]` configuration section.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhookSettings {
+    pub urls: Vec<String>,
+    /// Seconds to wait for each request
+    pub timeout: u64,
+    /// Number of retries after a failed request
+    pub retries: u32,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        WebhookSettings {
+            urls: Vec::new(),
+            timeout: 5,
+            retries: 3,
+        }
+    }
+}
+
+/// Body of the webhook requests.
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    event: &'static str,
+    #[serde(flatten)]
+    notification: &'a Notification,
+}
+
+/// Sends the notification as JSON in a POST request, in the background.
+/// Failed requests are retried with an exponential backoff.
+pub struct WebhookNotifier {
+    url: String,
+    timeout: time::Duration,
+    retries: u32,
+    backoff: time::Duration,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: &str, settings: &WebhookSettings) -> Self {
+        WebhookNotifier {
+            url: url.to_string(),
+            timeout: time::Duration::from_secs(settings.timeout),
+            retries: settings.retries,
+            backoff: time::Duration::from_secs(1),
+        }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify(&self, notification: &Notification) {
+        let url = self.url.clone();
+        let notification = notification.clone();
+        let retries = self.retries;
+        let mut backoff = self.backoff;
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(self.timeout))
+            .build()
+            .into();
+        thread::spawn(move || {
+            let payload = WebhookPayload {
+                event: match (notification.phase, notification.remaining) {
+                    (PomodoroState::Work, 0) => "work_start",
+                    (PomodoroState::Break, 0) => "break_start",
+                    (PomodoroState::Work, _) => "work_warning",
+                    (PomodoroState::Break, _) => "break_warning",
+                },
+                notification: &notification,
+            };
+            for _ in 0..=retries {
+                match agent.post(&url).send_json(&payload) {
+                    Ok(_) => return,
+                    // The request is wrong, sending it again won't help
+                    Err(ureq::Error::StatusCode(status))
+                        if (400..500).contains(&status) && status != 429 =>
+                    {
+                        return
+                    }
+                    Err(_) => {
+                        thread::sleep(backoff);
+                        backoff *= 2;
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Does nothing, to disable notifications.
+pub struct NoopNotifier;
+
+impl Notifier for NoopNotifier {
+    fn notify(&self, _notification: &Notification) {}
+}
+
+/// Sends the notifications to several notifiers.
+pub struct CompositeNotifier {
+    notifiers: Vec<Box<dyn Notifier>>,
+}
+
+impl CompositeNotifier {
+    pub fn new(notifiers: Vec<Box<dyn Notifier>>) -> Self {
+        CompositeNotifier { notifiers }
+    }
+}
+
+impl Notifier for CompositeNotifier {
+    fn notify(&self, notification: &Notification) {
+        for notifier in &self.notifiers {
+            notifier.notify(notification);
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Keeps the notifications it receives, for tests to check them.
+    #[derive(Clone, Default)]
+    pub(crate) struct RecordingNotifier {
+        pub(crate) notifications: Arc<Mutex<Vec<Notification>>>,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn notify(&self, notification: &Notification) {
+            self.notifications
+                .lock()
+                .unwrap()
+                .push(notification.clone());
+        }
+    }
+
+    pub(crate) fn notification() -> Notification {
+        Notification {
+            title: "Pomodoro Timer".to_string(),
+            message: "It's time to have a break.".to_string(),
+            phase: PomodoroState::Break,
+            remaining: 0,
+            work_duration: 1500,
+            break_duration: 300,
+            completed_sessions: 1,
+            task: Some("write tests".to_string()),
+        }
+    }
+
+    /// Minimal HTTP server answering the given statuses to successive
+    /// requests, and sending the body of each request to the channel.
+    fn serve(statuses: Vec<u16>) -> (String, std::sync::mpsc::Receiver<String>) {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                tx.send(String::from_utf8(body).unwrap()).unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .unwrap();
+            }
+        });
+        (url, rx)
+    }
+
+    fn webhook_notifier(url: &str, retries: u32) -> WebhookNotifier {
+        let mut notifier = WebhookNotifier::new(
+            url,
+            &WebhookSettings {
+                retries,
+                ..Default::default()
+            },
+        );
+        notifier.backoff = time::Duration::from_millis(10);
+        notifier
+    }
+
+    #[test]
+    fn test_webhook_notifier_payload() {
+        // Given
+        let (url, requests) = serve(vec![200]);
+        // When
+        webhook_notifier(&url, 0).notify(&notification());
+        // Then
+        let body = requests.recv_timeout(time::Duration::from_secs(5)).unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "event": "break_start",
+                "title": "Pomodoro Timer",
+                "message": "It's time to have a break.",
+                "phase": "break",
+                "remaining": 0,
+                "work_duration": 1500,
+                "break_duration": 300,
+                "completed_sessions": 1,
+                "task": "write tests",
+            })
+        );
+    }
+
+    #[test]
+    fn test_webhook_notifier_warning_event() {
+        // Given
+        let (url, requests) = serve(vec![200]);
+        let warning = Notification {
+            phase: PomodoroState::Work,
+            remaining: 60,
+            ..notification()
+        };
+        // When
+        webhook_notifier(&url, 0).notify(&warning);
+        // Then
+        let body = requests.recv_timeout(time::Duration::from_secs(5)).unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(payload["event"], "work_warning");
+        assert_eq!(payload["remaining"], 60);
+    }
+
+    #[test]
+    fn test_webhook_notifier_retries() {
+        // Given
+        let (url, requests) = serve(vec![503, 500, 200, 200]);
+        // When
+        webhook_notifier(&url, 3).notify(&notification());
+        // Then the request is sent until it succeeds
+        for _ in 0..3 {
+            requests.recv_timeout(time::Duration::from_secs(5)).unwrap();
+        }
+        assert!(requests
+            .recv_timeout(time::Duration::from_millis(200))
+            .is_err());
+    }
+
+    #[test]
+    fn test_webhook_notifier_does_not_retry_client_errors() {
+        // Given
+        let (url, requests) = serve(vec![404, 200]);
+        // When
+        webhook_notifier(&url, 3).notify(&notification());
+        // Then
+        requests.recv_timeout(time::Duration::from_secs(5)).unwrap();
+        assert!(requests
+            .recv_timeout(time::Duration::from_millis(200))
+            .is_err());
+    }
+
+    #[test]
+    fn test_composite_notifier() {
+        // Given
+        let first = RecordingNotifier::default();
+        let second = RecordingNotifier::default();
+        let notifier = CompositeNotifier::new(vec![
+            Box::new(first.clone()),
+            Box::new(NoopNotifier),
+            Box::new(second.clone()),
+        ]);
+        // When
+        notifier.notify(&notification());
+        // Then
+        assert_eq!(*first.notifications.lock().unwrap(), vec![notification()]);
+        assert_eq!(*second.notifications.lock().unwrap(), vec![notification()]);
+    }
+
+    #[test]
+    fn test_notification_action_ids() {
+        for action in NotificationAction::ALL {
+            assert_eq!(NotificationAction::from_id(action.id()), Some(action));
+        }
+        assert_eq!(NotificationAction::from_id("__closed"), None);
+    }
+
+    #[test]
+    fn test_terminal_notifier() {
+        // Given
+        let (tx, rx) = mpsc::channel();
+        let mut notifier = TerminalNotifier::new(tx);
+        notifier.tmux = false;
+        let mut notification = notification();
+        notification.title = "Pomodoro; Timer".to_string();
+        // When
+        notifier.notify(&notification);
+        // Then
+        assert_eq!(
+            String::from_utf8(rx.try_recv().unwrap()).unwrap(),
+            "\x07\x1b]9;Pomodoro, Timer: It's time to have a break.\x07\
+             \x1b]777;notify;Pomodoro, Timer;It's time to have a break.\x07"
+        );
+    }
+
+    #[test]
+    fn test_terminal_notifier_bell() {
+        let (tx, rx) = mpsc::channel();
+        TerminalNotifier::bell(tx).notify(&notification());
+        assert_eq!(rx.try_recv().unwrap(), b"\x07");
+    }
+
+    #[test]
+    fn test_tmux_passthrough() {
+        assert_eq!(
+            tmux_passthrough("\x1b]9;Break\x07"),
+            "\x1bPtmux;\x1b\x1b]9;Break\x07\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_command_notifier() {
+        // Given
+        let output = std::env::temp_dir().join(format!(
+            "pomodoro-tui-command-notifier-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&output);
+        let notifier = CommandNotifier::new(&format!(
+            "echo \"$POMO_PHASE: $POMO_MESSAGE\" > {}",
+            output.display()
+        ));
+        // When
+        notifier.notify(&notification());
+        // Then
+        let mut content = String::new();
+        for _ in 0..50 {
+            content = std::fs::read_to_string(&output).unwrap_or_default();
+            if !content.is_empty() {
+                break;
+            }
+            thread::sleep(time::Duration::from_millis(20));
+        }
+        assert_eq!(content, "break: It's time to have a break.\n");
+    }
+}
@@ -0,0 +1,203 @@
+use crossterm::event;
+use pomodoro_tui::commands::{self, Command};
+use pomodoro_tui::theme::Theme;
+use ratatui::{layout, style::Stylize, text, widgets, Frame};
+
+/// What the palette asks the app to do after a key.
+pub enum Outcome {
+    Pending,
+    Close,
+    Run(Command),
+}
+
+/// Prompt where commands like `set work 40m` are typed, with the matching
+/// commands listed below it.
+pub struct Palette {
+    input: String,
+    error: Option<String>,
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Palette {
+            input: String::new(),
+            error: None,
+        }
+    }
+
+    /// Shows why the last command couldn't be run.
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    fn suggestions(&self) -> Vec<&'static str> {
+        commands::suggestions(&self.input)
+    }
+
+    pub fn handle_key_event(&mut self, key_event: event::KeyEvent) -> Outcome {
+        match key_event.code {
+            event::KeyCode::Esc => return Outcome::Close,
+            event::KeyCode::Enter => match Command::parse(&self.input) {
+                Ok(command) => return Outcome::Run(command),
+                Err(err) => self.error = Some(err),
+            },
+            // Completes the input with the best match, up to its first
+            // value
+            event::KeyCode::Tab => {
+                if let Some(usage) = self.suggestions().first() {
+                    let completion = match usage.find('<') {
+                        Some(index) => &usage[..index],
+                        None => usage,
+                    };
+                    if !self.input.starts_with(completion.trim_end()) {
+                        self.input = completion.to_string();
+                        self.error = None;
+                    }
+                }
+            }
+            event::KeyCode::Backspace => {
+                self.input.pop();
+                self.error = None;
+            }
+            // Keys like Ctrl+C don't type
+            event::KeyCode::Char(c)
+                if (key_event.modifiers - event::KeyModifiers::SHIFT).is_empty() =>
+            {
+                self.input.push(c);
+                self.error = None;
+            }
+            _ => (),
+        }
+        Outcome::Pending
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: layout::Rect, theme: &Theme) {
+        let block = theme
+            .block()
+            .title(text::Line::from(" Command ".bold()).centered());
+        let inner = block.inner(area);
+        frame.render_widget(widgets::Clear, area);
+        frame.render_widget(block, area);
+
+        let [input_area, status_area, list_area] = layout::Layout::vertical([
+            layout::Constraint::Length(1),
+            layout::Constraint::Length(1),
+            layout::Constraint::Fill(1),
+        ])
+        .areas(inner);
+        let input = text::Line::from(vec![
+            ":".fg(theme.hint).bold(),
+            self.input.clone().into(),
+            "█".slow_blink(),
+        ]);
+        frame.render_widget(widgets::Paragraph::new(input), input_area);
+        if let Some(error) = &self.error {
+            frame.render_widget(
+                widgets::Paragraph::new(error.clone().fg(theme.error).italic()),
+                status_area,
+            );
+        }
+        let suggestions: Vec<text::Line> = self
+            .suggestions()
+            .into_iter()
+            .enumerate()
+            .map(|(index, usage)| match index {
+                0 => text::Line::from(usage.bold()),
+                _ => text::Line::styled(usage, theme.dim_style()),
+            })
+            .collect();
+        frame.render_widget(widgets::Paragraph::new(suggestions), list_area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time;
+
+    fn key(code: event::KeyCode) -> event::KeyEvent {
+        event::KeyEvent::new(code, event::KeyModifiers::NONE)
+    }
+
+    fn type_text(palette: &mut Palette, text: &str) {
+        for c in text.chars() {
+            palette.handle_key_event(key(event::KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_palette_runs_command() {
+        // Given
+        let mut palette = Palette::new();
+        type_text(&mut palette, "set work 40m");
+        // When
+        let outcome = palette.handle_key_event(key(event::KeyCode::Enter));
+        // Then
+        assert!(matches!(
+            outcome,
+            Outcome::Run(Command::SetWork(duration)) if duration == time::Duration::from_secs(2400)
+        ));
+    }
+
+    #[test]
+    fn test_palette_shows_error() {
+        // Given
+        let mut palette = Palette::new();
+        type_text(&mut palette, "set work soon");
+        // When
+        let outcome = palette.handle_key_event(key(event::KeyCode::Enter));
+        // Then the palette stays open with the error, until the input changes
+        assert!(matches!(outcome, Outcome::Pending));
+        assert!(palette.error.is_some());
+        palette.handle_key_event(key(event::KeyCode::Backspace));
+        assert_eq!(palette.input, "set work soo");
+        assert_eq!(palette.error, None);
+    }
+
+    #[test]
+    fn test_palette_closes() {
+        let mut palette = Palette::new();
+        type_text(&mut palette, "skip");
+        let outcome = palette.handle_key_event(key(event::KeyCode::Esc));
+        assert!(matches!(outcome, Outcome::Close));
+    }
+
+    #[test]
+    fn test_palette_tab_completion() {
+        // Given
+        let mut palette = Palette::new();
+        type_text(&mut palette, "sb");
+        // When
+        palette.handle_key_event(key(event::KeyCode::Tab));
+        // Then the input is completed up to the value
+        assert_eq!(palette.input, "set break ");
+        // A value already typed isn't replaced
+        type_text(&mut palette, "10m");
+        palette.handle_key_event(key(event::KeyCode::Tab));
+        assert_eq!(palette.input, "set break 10m");
+        // Commands without value are completed in full
+        let mut palette = Palette::new();
+        type_text(&mut palette, "ski");
+        palette.handle_key_event(key(event::KeyCode::Tab));
+        assert_eq!(palette.input, "skip");
+    }
+
+    #[test]
+    fn test_palette_ignores_ctrl_and_alt() {
+        // Given
+        let mut palette = Palette::new();
+        type_text(&mut palette, "sk");
+        // When
+        for modifiers in [event::KeyModifiers::CONTROL, event::KeyModifiers::ALT] {
+            let outcome = palette
+                .handle_key_event(event::KeyEvent::new(event::KeyCode::Char('c'), modifiers));
+            assert!(matches!(outcome, Outcome::Pending));
+        }
+        // Then only the shifted keys are typed
+        palette.handle_key_event(event::KeyEvent::new(
+            event::KeyCode::Char('I'),
+            event::KeyModifiers::SHIFT,
+        ));
+        assert_eq!(palette.input, "skI");
+    }
+}
@@ -0,0 +1,232 @@
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time;
+
+use serde::Deserialize;
+
+/// Sound played when no other one is given, embedded in the binary so that
+/// it doesn't depend on where the sources were built.
+pub const DEFAULT_SOUND: &[u8] = include_bytes!("../default_sound.mp3");
+
+/// Sound to play, read from a file or embedded in the binary.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sound {
+    File(PathBuf),
+    Embedded(&'static [u8]),
+}
+
+impl Default for Sound {
+    fn default() -> Self {
+        Sound::Embedded(DEFAULT_SOUND)
+    }
+}
+
+/// Moment at which a sound is played.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SoundEvent {
+    WorkStart,
+    BreakStart,
+    LongBreakStart,
+    Warning,
+    GoalReached,
+}
+
+/// Sounds played on timer events, from the `[sounds]` configuration
+/// section. Events without a sound play the default one.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Sounds {
+    pub work_start: Option<PathBuf>,
+    pub break_start: Option<PathBuf>,
+    pub long_break_start: Option<PathBuf>,
+    pub warning: Option<PathBuf>,
+    pub goal_reached: Option<PathBuf>,
+    /// Volume of the sounds, from 0 to 100
+    pub volume: u8,
+    /// Seconds after which a sound is stopped, the whole sound is played
+    /// otherwise
+    pub max_duration: Option<u64>,
+}
+
+impl Default for Sounds {
+    fn default() -> Self {
+        Sounds {
+            work_start: None,
+            break_start: None,
+            long_break_start: None,
+            warning: None,
+            goal_reached: None,
+            volume: 100,
+            max_duration: None,
+        }
+    }
+}
+
+impl Sounds {
+    /// Sound of the event, if one is set. The long break and goal reached
+    /// sounds fall back to the break start sound.
+    pub fn path(&self, event: SoundEvent) -> Option<&Path> {
+        match event {
+            SoundEvent::WorkStart => self.work_start.as_deref(),
+            SoundEvent::BreakStart => self.break_start.as_deref(),
+            SoundEvent::LongBreakStart => self
+                .long_break_start
+                .as_deref()
+                .or(self.break_start.as_deref()),
+            SoundEvent::Warning => self.warning.as_deref(),
+            SoundEvent::GoalReached => self.goal_reached.as_deref().or(self.break_start.as_deref()),
+        }
+    }
+
+    /// Factor applied to the samples for the volume.
+    pub fn amplitude(&self) -> f32 {
+        amplitude(self.volume)
+    }
+}
+
+/// Factor applied to the samples for a volume from 0 to 100.
+pub fn amplitude(volume: u8) -> f32 {
+    f32::from(volume.min(100)) / 100.0
+}
+
+/// Something playing the sounds of the timer, without waiting for their end.
+pub trait Player: Send {
+    /// Plays the sound for at most `max_duration`, the volume being the
+    /// factor applied to the samples.
+    fn play(&self, sound: Sound, volume: f32, max_duration: Option<time::Duration>);
+}
+
+/// Plays the sounds on the default audio output, each from its own thread.
+pub struct AudioPlayer;
+
+impl Player for AudioPlayer {
+    fn play(&self, sound: Sound, volume: f32, max_duration: Option<time::Duration>) {
+        thread::spawn(move || crate::sound_play(&sound, volume, max_duration));
+    }
+}
+
+/// Settings of the alarm, from the `[alarm]` configuration section.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlarmSettings {
+    /// Whether the sound of a phase change is played again until the user
+    /// acknowledges it
+    pub insistent: bool,
+    /// Seconds between two rings of an insistent alarm
+    pub interval: u64,
+    /// Whether each ring is louder than the previous one
+    pub escalate: bool,
+}
+
+impl Default for AlarmSettings {
+    fn default() -> Self {
+        AlarmSettings {
+            insistent: false,
+            interval: 10,
+            escalate: false,
+        }
+    }
+}
+
+impl AlarmSettings {
+    /// Volume of the alarm after it rang again the given number of times,
+    /// from the volume of the sounds. An escalating alarm starts at half of
+    /// it, and gets back to it in five rings.
+    pub fn volume(&self, volume: u8, rings: u32) -> u8 {
+        let volume = volume.min(100);
+        match self.escalate {
+            true => {
+                let step = u32::from(volume / 10).max(1);
+                (u32::from(volume / 2) + rings * step).min(u32::from(volume)) as u8
+            }
+            false => volume,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Player keeping the volumes of the sounds, instead of playing them.
+    #[derive(Clone, Default)]
+    pub(crate) struct RecordingPlayer {
+        pub(crate) volumes: Arc<Mutex<Vec<f32>>>,
+    }
+
+    impl Player for RecordingPlayer {
+        fn play(&self, _sound: Sound, volume: f32, _max_duration: Option<time::Duration>) {
+            self.volumes.lock().unwrap().push(volume);
+        }
+    }
+
+    #[test]
+    fn test_default_sound_decodes() {
+        assert!(rodio::Decoder::new(std::io::Cursor::new(DEFAULT_SOUND)).is_ok());
+    }
+
+    #[test]
+    fn test_sounds_path() {
+        // Given
+        let sounds = Sounds {
+            work_start: Some(PathBuf::from("work.mp3")),
+            break_start: Some(PathBuf::from("break.mp3")),
+            goal_reached: Some(PathBuf::from("goal.mp3")),
+            ..Default::default()
+        };
+        // Then
+        assert_eq!(
+            sounds.path(SoundEvent::WorkStart),
+            Some(Path::new("work.mp3"))
+        );
+        assert_eq!(
+            sounds.path(SoundEvent::LongBreakStart),
+            Some(Path::new("break.mp3"))
+        );
+        assert_eq!(
+            sounds.path(SoundEvent::GoalReached),
+            Some(Path::new("goal.mp3"))
+        );
+        assert_eq!(sounds.path(SoundEvent::Warning), None);
+    }
+
+    #[test]
+    fn test_alarm_volume() {
+        let alarm = AlarmSettings {
+            escalate: true,
+            ..Default::default()
+        };
+        assert_eq!(alarm.volume(50, 0), 25);
+        assert_eq!(alarm.volume(50, 2), 35);
+        assert_eq!(alarm.volume(50, 8), 50);
+        assert_eq!(AlarmSettings::default().volume(50, 8), 50);
+    }
+
+    #[test]
+    fn test_alarm_volume_default() {
+        // Given the default volume of the sounds
+        let volume = Sounds::default().volume;
+        let alarm = AlarmSettings {
+            insistent: true,
+            escalate: true,
+            ..Default::default()
+        };
+        // Then an escalating alarm gets louder up to it
+        assert_eq!(alarm.volume(volume, 0), 50);
+        assert_eq!(alarm.volume(volume, 1), 60);
+        assert_eq!(alarm.volume(volume, 5), 100);
+        assert_eq!(alarm.volume(volume, 9), 100);
+    }
+
+    #[test]
+    fn test_sounds_amplitude() {
+        let sounds = |volume| Sounds {
+            volume,
+            ..Default::default()
+        };
+        assert_eq!(sounds(100).amplitude(), 1.0);
+        assert_eq!(sounds(25).amplitude(), 0.25);
+        assert_eq!(sounds(200).amplitude(), 1.0);
+    }
+}
@@ -0,0 +1,231 @@
+use std::env;
+use std::process;
+use std::thread;
+
+use serde::Deserialize;
+
+use crate::messages::Messages;
+use crate::notifier::{Notification, Notifier};
+
+/// Program reading the notifications out loud.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    EspeakNg,
+    SpdSay,
+    /// The `say` command of MacOS
+    Say,
+}
+
+impl Backend {
+    fn program(&self) -> &'static str {
+        match self {
+            Backend::EspeakNg => "espeak-ng",
+            Backend::SpdSay => "spd-say",
+            Backend::Say => "say",
+        }
+    }
+
+    /// Backend of the platform, or the first one installed on Linux.
+    pub fn detect() -> Option<Self> {
+        if cfg!(target_os = "macos") {
+            return Some(Backend::Say);
+        }
+        [Backend::EspeakNg, Backend::SpdSay]
+            .into_iter()
+            .find(|backend| is_installed(backend.program()))
+    }
+}
+
+fn is_installed(program: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|path| env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+/// Settings of the speech, from the `[speech]` configuration section.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpeechSettings {
+    /// Detected from the platform and the installed programs by default
+    pub backend: Option<Backend>,
+    /// Voice of the backend, its default one otherwise
+    pub voice: Option<String>,
+    /// Words per minute
+    pub rate: Option<u32>,
+    /// Text read out loud, `{title}`, `{message}` and `{phase}` being
+    /// replaced by those of the notification
+    pub template: String,
+}
+
+impl Default for SpeechSettings {
+    fn default() -> Self {
+        SpeechSettings {
+            backend: None,
+            voice: None,
+            rate: None,
+            template: "{message}".to_string(),
+        }
+    }
+}
+
+/// Reads the notifications out loud, with `espeak-ng` or `spd-say` on Linux
+/// and `say` on MacOS.
+pub struct SpeechNotifier {
+    backend: Backend,
+    settings: SpeechSettings,
+    /// Names of the phases, for `{phase}`
+    messages: Messages,
+}
+
+impl SpeechNotifier {
+    /// Returns `None` when no backend is set nor installed.
+    pub fn new(settings: &SpeechSettings) -> Option<Self> {
+        let backend = settings.backend.or_else(Backend::detect)?;
+        Some(SpeechNotifier {
+            backend,
+            settings: settings.clone(),
+            messages: Messages::default(),
+        })
+    }
+
+    /// Reads the phases with the names of the given messages.
+    pub fn with_messages(mut self, messages: Messages) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    fn command(&self, text: &str) -> process::Command {
+        let mut cmd = process::Command::new(self.backend.program());
+        match self.backend {
+            Backend::EspeakNg | Backend::Say => {
+                if let Some(voice) = &self.settings.voice {
+                    cmd.arg("-v").arg(voice);
+                }
+                if let Some(rate) = self.settings.rate {
+                    let flag = match self.backend {
+                        Backend::EspeakNg => "-s",
+                        _ => "-r",
+                    };
+                    cmd.arg(flag).arg(rate.to_string());
+                }
+            }
+            Backend::SpdSay => {
+                if let Some(voice) = &self.settings.voice {
+                    cmd.arg("-y").arg(voice);
+                }
+                // The rate of speech-dispatcher goes from -100 to 100, 0
+                // being about 180 words per minute
+                if let Some(rate) = self.settings.rate {
+                    let rate = ((i64::from(rate) - 180) / 2).clamp(-100, 100);
+                    cmd.arg("-r").arg(rate.to_string());
+                }
+                cmd.arg("--wait");
+            }
+        }
+        cmd.arg(text)
+            .stdin(process::Stdio::null())
+            .stdout(process::Stdio::null())
+            .stderr(process::Stdio::null());
+        cmd
+    }
+}
+
+impl Notifier for SpeechNotifier {
+    fn notify(&self, notification: &Notification) {
+        let text = render(&self.settings.template, notification, &self.messages);
+        // Wait for the command in the background, to not leave a zombie
+        // process behind
+        if let Ok(mut child) = self.command(&text).spawn() {
+            thread::spawn(move || child.wait());
+        }
+    }
+}
+
+fn render(template: &str, notification: &Notification, messages: &Messages) -> String {
+    template
+        .replace("{title}", &notification.title)
+        .replace("{message}", &notification.message)
+        .replace("{phase}", messages.phase(notification.phase))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifier::tests::notification;
+
+    fn args(notifier: &SpeechNotifier) -> Vec<String> {
+        let cmd = notifier.command("Break time");
+        std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn notifier(backend: Backend) -> SpeechNotifier {
+        SpeechNotifier::new(&SpeechSettings {
+            backend: Some(backend),
+            voice: Some("en-gb".to_string()),
+            rate: Some(160),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_speech_commands() {
+        assert_eq!(
+            args(&notifier(Backend::EspeakNg)),
+            ["espeak-ng", "-v", "en-gb", "-s", "160", "Break time"]
+        );
+        assert_eq!(
+            args(&notifier(Backend::SpdSay)),
+            [
+                "spd-say",
+                "-y",
+                "en-gb",
+                "-r",
+                "-10",
+                "--wait",
+                "Break time"
+            ]
+        );
+        assert_eq!(
+            args(&notifier(Backend::Say)),
+            ["say", "-v", "en-gb", "-r", "160", "Break time"]
+        );
+    }
+
+    #[test]
+    fn test_speech_default_voice() {
+        let notifier = SpeechNotifier::new(&SpeechSettings {
+            backend: Some(Backend::Say),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(args(&notifier), ["say", "Break time"]);
+    }
+
+    #[test]
+    fn test_speech_template() {
+        assert_eq!(
+            render(
+                "{title}: {message} ({phase})",
+                &notification(),
+                &Messages::default()
+            ),
+            "Pomodoro Timer: It's time to have a break. (break)"
+        );
+        let messages = Messages {
+            break_phase: "descanso".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(render("{phase}", &notification(), &messages), "descanso");
+    }
+
+    #[test]
+    fn test_speech_settings_backend() {
+        let settings: SpeechSettings = toml::from_str("backend = \"spd-say\"\n").unwrap();
+        assert_eq!(settings.backend, Some(Backend::SpdSay));
+        assert_eq!(settings.template, "{message}");
+    }
+}
@@ -0,0 +1,145 @@
+use chrono::{Datelike, Days, NaiveDate, Timelike};
+
+use crate::history::Session;
+use crate::PomodoroState;
+
+fn work_sessions(sessions: &[Session]) -> impl Iterator<Item = &Session> {
+    sessions
+        .iter()
+        .filter(|session| session.phase == PomodoroState::Work)
+}
+
+/// Focus minutes of each of the `days` days ending on `today`, oldest first.
+pub fn focus_minutes_per_day(
+    sessions: &[Session],
+    today: NaiveDate,
+    days: u64,
+) -> Vec<(NaiveDate, u64)> {
+    let first_day = today - Days::new(days.saturating_sub(1));
+    let mut minutes: Vec<(NaiveDate, u64)> = first_day
+        .iter_days()
+        .take(days as usize)
+        .map(|day| (day, 0))
+        .collect();
+    for session in work_sessions(sessions) {
+        let day = session.start.date_naive();
+        if day < first_day || day > today {
+            continue;
+        }
+        let index = (day - first_day).num_days() as usize;
+        minutes[index].1 += session.duration / 60;
+    }
+    minutes
+}
+
+/// Focus minutes by weekday (Monday first) and hour of the day at which the
+/// sessions started.
+pub fn weekday_hour_heatmap(sessions: &[Session]) -> [[u64; 24]; 7] {
+    let mut heatmap = [[0; 24]; 7];
+    for session in work_sessions(sessions) {
+        let weekday = session.start.weekday().num_days_from_monday() as usize;
+        let hour = session.start.hour() as usize;
+        heatmap[weekday][hour] += session.duration / 60;
+    }
+    heatmap
+}
+
+/// Number of consecutive days with at least one completed work session,
+/// ending today. A streak ending yesterday still counts, as today is not
+/// over yet.
+pub fn streak(sessions: &[Session], today: NaiveDate) -> u32 {
+    let days: std::collections::HashSet<NaiveDate> = work_sessions(sessions)
+        .map(|session| session.start.date_naive())
+        .collect();
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today - Days::new(1)
+    };
+    let mut streak = 0;
+    while days.contains(&day) {
+        streak += 1;
+        day = day - Days::new(1);
+    }
+    streak
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn session(day: u32, hour: u32, phase: PomodoroState, minutes: u64) -> Session {
+        Session {
+            start: Local.with_ymd_and_hms(2025, 3, day, hour, 0, 0).unwrap(),
+            phase,
+            duration: minutes * 60,
+            task: None,
+            tags: Vec::new(),
+            notes: String::new(),
+        }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, 14).unwrap()
+    }
+
+    #[test]
+    fn test_focus_minutes_per_day() {
+        // Given
+        let sessions = vec![
+            session(1, 9, PomodoroState::Work, 25),
+            session(13, 9, PomodoroState::Work, 25),
+            session(14, 9, PomodoroState::Work, 25),
+            session(14, 10, PomodoroState::Work, 50),
+            session(14, 10, PomodoroState::Break, 5),
+        ];
+        // When
+        let minutes = focus_minutes_per_day(&sessions, today(), 7);
+        // Then
+        assert_eq!(minutes.len(), 7);
+        assert_eq!(
+            minutes[0],
+            (NaiveDate::from_ymd_opt(2025, 3, 8).unwrap(), 0)
+        );
+        assert_eq!(minutes[5].1, 25);
+        assert_eq!(minutes[6], (today(), 75));
+    }
+
+    #[test]
+    fn test_weekday_hour_heatmap() {
+        // Given 2025-03-14 is a Friday
+        let sessions = vec![
+            session(14, 9, PomodoroState::Work, 25),
+            session(14, 9, PomodoroState::Work, 25),
+            session(10, 15, PomodoroState::Work, 25),
+            session(10, 15, PomodoroState::Break, 5),
+        ];
+        // When
+        let heatmap = weekday_hour_heatmap(&sessions);
+        // Then
+        assert_eq!(heatmap[4][9], 50);
+        assert_eq!(heatmap[0][15], 25);
+        assert_eq!(heatmap.iter().flatten().sum::<u64>(), 75);
+    }
+
+    #[test]
+    fn test_streak() {
+        // Given
+        let sessions = vec![
+            session(10, 9, PomodoroState::Work, 25),
+            session(12, 9, PomodoroState::Work, 25),
+            session(13, 9, PomodoroState::Work, 25),
+            session(14, 9, PomodoroState::Break, 5),
+        ];
+        // Then the streak still counts when nothing is done yet today
+        assert_eq!(streak(&sessions, today()), 2);
+        // When today is done
+        let mut sessions = sessions;
+        sessions.push(session(14, 10, PomodoroState::Work, 25));
+        // Then
+        assert_eq!(streak(&sessions, today()), 3);
+        // When the streak is broken
+        assert_eq!(streak(&sessions, today() + Days::new(2)), 0);
+    }
+}
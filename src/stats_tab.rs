@@ -0,0 +1,107 @@
+use chrono::Local;
+use pomodoro_tui::{history::History, stats, theme::Theme};
+use ratatui::{
+    layout,
+    style::{self, Stylize},
+    text, widgets, Frame,
+};
+
+const DAYS: u64 = 30;
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const SHADES: [&str; 5] = ["··", "░░", "▒▒", "▓▓", "██"];
+
+/// Screen summarizing the focus habits recorded in the history.
+pub fn draw(frame: &mut Frame, area: layout::Rect, history: &History, theme: &Theme) {
+    let sessions = history.sessions();
+    let today = Local::now().date_naive();
+    let per_day = stats::focus_minutes_per_day(sessions, today, DAYS);
+
+    let [summary_area, chart_area, heatmap_area] = layout::Layout::vertical([
+        layout::Constraint::Length(2),
+        layout::Constraint::Fill(1),
+        layout::Constraint::Length(9),
+    ])
+    .areas(area);
+
+    let today_minutes = per_day.last().map_or(0, |(_, minutes)| *minutes);
+    let total_minutes: u64 = per_day.iter().map(|(_, minutes)| minutes).sum();
+    let summary = text::Line::from(vec![
+        " Streak: ".bold(),
+        format!("{} day(s)", stats::streak(sessions, today)).into(),
+        "   Today: ".bold(),
+        format_minutes(today_minutes).into(),
+        format!("   Last {} days: ", DAYS).bold(),
+        format_minutes(total_minutes).into(),
+    ]);
+    frame.render_widget(widgets::Paragraph::new(summary), summary_area);
+
+    frame.render_widget(get_bar_chart(&per_day, chart_area.width, theme), chart_area);
+    frame.render_widget(
+        get_heatmap(&stats::weekday_hour_heatmap(sessions), theme),
+        heatmap_area,
+    );
+}
+
+fn format_minutes(minutes: u64) -> String {
+    format!("{}h{:02}", minutes / 60, minutes % 60)
+}
+
+fn get_bar_chart<'a>(
+    per_day: &'a [(chrono::NaiveDate, u64)],
+    width: u16,
+    theme: &Theme,
+) -> widgets::BarChart<'a> {
+    let bars: Vec<widgets::Bar> = per_day
+        .iter()
+        .map(|(day, minutes)| {
+            widgets::Bar::default()
+                .value(*minutes)
+                .text_value(String::new())
+                .label(text::Line::from(day.format("%d").to_string()))
+        })
+        .collect();
+    // Make the bars as wide as possible while fitting every day
+    let bar_width = (width.saturating_sub(2) / per_day.len().max(1) as u16)
+        .saturating_sub(1)
+        .max(1);
+    widgets::BarChart::default()
+        .block(
+            widgets::Block::new()
+                .title(format!(" Focus minutes, last {} days ", DAYS).bold())
+                .padding(widgets::Padding::horizontal(1)),
+        )
+        .data(widgets::BarGroup::default().bars(&bars))
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_style(style::Style::new().fg(theme.work))
+}
+
+fn get_heatmap(heatmap: &[[u64; 24]; 7], theme: &Theme) -> widgets::Paragraph<'static> {
+    let max = heatmap.iter().flatten().copied().max().unwrap_or(0);
+
+    let mut hours = String::from("     ");
+    for hour in (0..24).step_by(3) {
+        hours.push_str(&format!("{:<6}", hour));
+    }
+    let mut lines = vec![
+        text::Line::from(" Focus by weekday and hour".bold()),
+        text::Line::from(text::Span::styled(hours, theme.dim_style())),
+    ];
+    for (weekday, minutes) in WEEKDAYS.iter().zip(heatmap) {
+        let mut spans = vec![text::Span::from(format!(" {} ", weekday))];
+        spans.extend(minutes.iter().map(|&minutes| {
+            let shade = match (minutes, max) {
+                (0, _) => 0,
+                (minutes, max) => 1 + ((minutes * 4 - 1) / max) as usize,
+            };
+            let span = text::Span::from(SHADES[shade.min(4)]);
+            if shade == 0 {
+                span.style(theme.dim_style())
+            } else {
+                span.fg(theme.work)
+            }
+        }));
+        lines.push(text::Line::from(spans));
+    }
+    widgets::Paragraph::new(lines)
+}
@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+use std::env;
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, BorderType};
+use serde::Deserialize;
+
+use crate::PomodoroState;
+
+/// Names of the built-in themes, the first one being the default.
+pub const BUILTIN_THEMES: [&str; 5] = ["dark", "light", "solarized", "gruvbox", "high-contrast"];
+
+/// Lines of the borders of the windows.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Border {
+    Plain,
+    Rounded,
+    Double,
+    Thick,
+}
+
+impl Border {
+    pub fn border_type(&self) -> BorderType {
+        match self {
+            Border::Plain => BorderType::Plain,
+            Border::Rounded => BorderType::Rounded,
+            Border::Double => BorderType::Double,
+            Border::Thick => BorderType::Thick,
+        }
+    }
+}
+
+/// Colors and borders of the interface. `Color::Reset` is the color of the
+/// terminal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub work: Color,
+    pub break_time: Color,
+    /// Keys of the hints
+    pub hint: Color,
+    pub border: Border,
+    pub border_color: Color,
+    pub background: Color,
+    pub text: Color,
+    /// Secondary texts, and the unfilled part of the gauges
+    pub dim: Color,
+    pub error: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::builtin("dark").expect("the dark theme is built in")
+    }
+}
+
+impl Theme {
+    /// Built-in theme of the given name, if any.
+    pub fn builtin(name: &str) -> Option<Self> {
+        let hex = |rgb: u32| Color::from_u32(rgb);
+        let theme = match name {
+            "dark" => Theme {
+                work: Color::Blue,
+                break_time: Color::Green,
+                hint: Color::Blue,
+                border: Border::Thick,
+                border_color: Color::Reset,
+                background: Color::Reset,
+                text: Color::Reset,
+                dim: Color::DarkGray,
+                error: Color::Red,
+            },
+            "light" => Theme {
+                work: hex(0x1e66f5),
+                break_time: hex(0x40a02b),
+                hint: hex(0x1e66f5),
+                border: Border::Rounded,
+                border_color: hex(0x7c7f93),
+                background: hex(0xeff1f5),
+                text: hex(0x4c4f69),
+                dim: hex(0xacb0be),
+                error: hex(0xd20f39),
+            },
+            "solarized" => Theme {
+                work: hex(0x268bd2),
+                break_time: hex(0x859900),
+                hint: hex(0xb58900),
+                border: Border::Rounded,
+                border_color: hex(0x586e75),
+                background: hex(0x002b36),
+                text: hex(0x839496),
+                dim: hex(0x586e75),
+                error: hex(0xdc322f),
+            },
+            "gruvbox" => Theme {
+                work: hex(0x83a598),
+                break_time: hex(0xb8bb26),
+                hint: hex(0xfabd2f),
+                border: Border::Thick,
+                border_color: hex(0xa89984),
+                background: hex(0x282828),
+                text: hex(0xebdbb2),
+                dim: hex(0x665c54),
+                error: hex(0xfb4934),
+            },
+            "high-contrast" => Theme {
+                work: Color::LightCyan,
+                break_time: Color::LightGreen,
+                hint: Color::LightYellow,
+                border: Border::Double,
+                border_color: Color::White,
+                background: Color::Black,
+                text: Color::White,
+                dim: Color::Gray,
+                error: Color::LightRed,
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+
+    /// Theme without colors, for the `NO_COLOR` environment variable.
+    pub fn monochrome() -> Self {
+        Theme {
+            work: Color::Reset,
+            break_time: Color::Reset,
+            hint: Color::Reset,
+            border: Border::Thick,
+            border_color: Color::Reset,
+            background: Color::Reset,
+            text: Color::Reset,
+            dim: Color::Reset,
+            error: Color::Reset,
+        }
+    }
+
+    /// Whether colors are disabled with the `NO_COLOR` environment variable,
+    /// see <https://no-color.org>.
+    pub fn is_color_disabled() -> bool {
+        env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+    }
+
+    /// Theme of the given name, among those of the configuration and the
+    /// built-in ones.
+    pub fn resolve(name: &str, themes: &BTreeMap<String, ThemeSettings>) -> Result<Self, String> {
+        let mut names = vec![name];
+        let mut settings = Vec::new();
+        let mut theme = loop {
+            let name = names[names.len() - 1];
+            let Some(custom) = themes.get(name) else {
+                break Theme::builtin(name).ok_or_else(|| format!("unknown theme `{}`", name))?;
+            };
+            settings.push(custom);
+            let base = custom.base.as_deref().unwrap_or(BUILTIN_THEMES[0]);
+            if names.contains(&base) {
+                return Err(format!("theme `{}` is based on itself", base));
+            }
+            names.push(base);
+        };
+        // Colors of the themes win over those of the themes they are based on
+        for custom in settings.into_iter().rev() {
+            custom.apply(&mut theme);
+        }
+        Ok(theme)
+    }
+
+    pub fn phase(&self, phase: PomodoroState) -> Color {
+        match phase {
+            PomodoroState::Work => self.work,
+            PomodoroState::Break => self.break_time,
+        }
+    }
+
+    /// Style of the windows, with the text and background colors.
+    pub fn base_style(&self) -> Style {
+        Style::new().fg(self.text).bg(self.background)
+    }
+
+    /// Bordered window, with the border and the colors of the theme.
+    pub fn block(&self) -> Block<'static> {
+        Block::bordered()
+            .border_type(self.border.border_type())
+            .border_style(Style::new().fg(self.border_color))
+            .style(self.base_style())
+    }
+
+    /// Style of the secondary texts, dimmed when there are no colors.
+    pub fn dim_style(&self) -> Style {
+        match self.dim {
+            Color::Reset => Style::new().add_modifier(Modifier::DIM),
+            dim => Style::new().fg(dim),
+        }
+    }
+}
+
+/// Theme of a `[themes.<name>]` configuration section, changing some colors
+/// of the theme it is based on.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeSettings {
+    /// Built-in or configured theme, `dark` by default
+    pub base: Option<String>,
+    pub work: Option<Color>,
+    #[serde(rename = "break")]
+    pub break_time: Option<Color>,
+    pub hint: Option<Color>,
+    pub border: Option<Border>,
+    pub border_color: Option<Color>,
+    pub background: Option<Color>,
+    pub text: Option<Color>,
+    pub dim: Option<Color>,
+    pub error: Option<Color>,
+}
+
+impl ThemeSettings {
+    fn apply(&self, theme: &mut Theme) {
+        let colors = [
+            (&mut theme.work, self.work),
+            (&mut theme.break_time, self.break_time),
+            (&mut theme.hint, self.hint),
+            (&mut theme.border_color, self.border_color),
+            (&mut theme.background, self.background),
+            (&mut theme.text, self.text),
+            (&mut theme.dim, self.dim),
+            (&mut theme.error, self.error),
+        ];
+        for (color, setting) in colors {
+            if let Some(setting) = setting {
+                *color = setting;
+            }
+        }
+        if let Some(border) = self.border {
+            theme.border = border;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn themes(content: &str) -> BTreeMap<String, ThemeSettings> {
+        toml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn test_builtin_themes() {
+        for name in BUILTIN_THEMES {
+            assert!(Theme::resolve(name, &BTreeMap::new()).is_ok());
+        }
+        assert_eq!(
+            Theme::resolve("neon", &BTreeMap::new()),
+            Err("unknown theme `neon`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_custom_theme() {
+        // Given
+        let themes = themes(
+            r##"
+            [mine]
+            base = "nord"
+            work = "magenta"
+
+            [nord]
+            base = "solarized"
+            break = "#a3be8c"
+            border = "plain"
+            "##,
+        );
+        // When
+        let theme = Theme::resolve("mine", &themes).unwrap();
+        // Then
+        assert_eq!(theme.work, Color::Magenta);
+        assert_eq!(theme.break_time, Color::Rgb(0xa3, 0xbe, 0x8c));
+        assert_eq!(theme.border, Border::Plain);
+        assert_eq!(
+            theme.background,
+            Theme::builtin("solarized").unwrap().background
+        );
+    }
+
+    #[test]
+    fn test_custom_theme_default_base() {
+        let themes = themes("[mine]\nhint = \"yellow\"\n");
+        let theme = Theme::resolve("mine", &themes).unwrap();
+        assert_eq!(theme.hint, Color::Yellow);
+        assert_eq!(theme.work, Theme::default().work);
+    }
+
+    #[test]
+    fn test_custom_theme_cycle() {
+        let themes = themes("[a]\nbase = \"b\"\n[b]\nbase = \"a\"\n");
+        assert_eq!(
+            Theme::resolve("a", &themes),
+            Err("theme `a` is based on itself".to_string())
+        );
+    }
+
+    #[test]
+    fn test_theme_settings_invalid_color() {
+        assert!(toml::from_str::<ThemeSettings>("work = \"blurple\"\n").is_err());
+    }
+}